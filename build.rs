@@ -1,8 +1,12 @@
 use anyhow::*;
+#[cfg(feature = "gui")]
 use glob::glob;
+#[cfg(feature = "gui")]
 use std::fs::{read_to_string, write};
+#[cfg(feature = "gui")]
 use std::path::PathBuf;
 
+#[cfg(feature = "gui")]
 struct ShaderData {
     src: String,
     src_path: PathBuf,
@@ -10,6 +14,7 @@ struct ShaderData {
     kind: shaderc::ShaderKind,
 }
 
+#[cfg(feature = "gui")]
 impl ShaderData {
     pub fn load(src_path: PathBuf) -> Result<Self> {
         let extension = src_path
@@ -37,6 +42,15 @@ impl ShaderData {
 }
 
 fn main() -> Result<()> {
+    // the headless library has nothing to draw, so no shaders and no shaderc
+    #[cfg(feature = "gui")]
+    compile_shaders()?;
+
+    Ok(())
+}
+
+#[cfg(feature = "gui")]
+fn compile_shaders() -> Result<()> {
     println!("cargo:rerun-if-changed=src/shaders/*.vert");
     println!("cargo:rerun-if-changed=src/shaders/*.comp");
     println!("cargo:rerun-if-changed=src/shaders/*.frag");
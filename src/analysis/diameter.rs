@@ -0,0 +1,113 @@
+use crate::analysis::distances_from;
+use crate::grids::{CellKind, Grid};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Diameter {
+    pub ends: ((usize, usize), (usize, usize)),
+    // steps between the two ends
+    pub length: usize,
+}
+
+// two bfs passes: the farthest cell from anywhere is one end of the longest path, the farthest
+// cell from that end is the other. exact for perfect mazes, a lower bound when there are loops,
+// and only covers the region of the first open cell when the maze is disconnected
+pub fn find_diameter(grid: &dyn Grid) -> Option<Diameter> {
+    let dims = grid.dims();
+    let first_open = grid
+        .cells()
+        .iter()
+        .position(|cell| *cell != CellKind::Wall)?;
+    let first_open = (first_open / dims.columns, first_open % dims.columns);
+
+    let (one, _) = distances_from(grid, first_open).farthest();
+    let (two, length) = distances_from(grid, one).farthest();
+
+    Some(Diameter {
+        ends: (one, two),
+        length,
+    })
+}
+
+// moves start and goal onto the ends of the diameter
+pub fn place_ends(grid: &mut dyn Grid, diameter: &Diameter) {
+    grid.set_start(Some(diameter.ends.0));
+    grid.set_goal(Some(diameter.ends.1));
+}
+
+#[cfg(test)]
+mod test_diameter {
+    use super::*;
+    use crate::grids::block_grid::BlockGrid;
+    use crate::grids::wall_grid::WallGrid;
+
+    #[test]
+    fn wall_grid_spiral() {
+        // 3x3 snake: row 0 left to right, row 1 right to left, row 2 left to right
+        let mut grid = WallGrid::with_dims(3, 3);
+        grid.clear_wall_between((0, 0), (0, 1)).unwrap();
+        grid.clear_wall_between((0, 1), (0, 2)).unwrap();
+        grid.clear_wall_between((0, 2), (1, 2)).unwrap();
+        grid.clear_wall_between((1, 2), (1, 1)).unwrap();
+        grid.clear_wall_between((1, 1), (1, 0)).unwrap();
+        grid.clear_wall_between((1, 0), (2, 0)).unwrap();
+        grid.clear_wall_between((2, 0), (2, 1)).unwrap();
+        grid.clear_wall_between((2, 1), (2, 2)).unwrap();
+
+        let diameter = find_diameter(&grid).unwrap();
+        assert_eq!(diameter.length, 8);
+        assert_eq!(diameter.ends, ((2, 2), (0, 0)));
+
+        place_ends(&mut grid, &diameter);
+        assert_eq!(grid.start, Some((2, 2)));
+        assert_eq!(grid.goal, Some((0, 0)));
+        assert_eq!(grid.get_cell(0, 0), CellKind::Goal);
+    }
+
+    #[test]
+    fn swapping_the_ends_keeps_both() {
+        let mut grid = WallGrid::with_dims(1, 4);
+        for column in 0..3 {
+            grid.clear_wall_between((0, column), (0, column + 1))
+                .unwrap();
+        }
+        let diameter = find_diameter(&grid).unwrap();
+        place_ends(&mut grid, &diameter);
+
+        // the new start is the old goal and the other way round
+        let (one, two) = diameter.ends;
+        let swapped = Diameter {
+            ends: (two, one),
+            ..diameter
+        };
+        place_ends(&mut grid, &swapped);
+        assert_eq!(grid.start(), Some(two));
+        assert_eq!(grid.goals(), vec![one]);
+        assert_eq!(grid.get_cell(two.0, two.1), CellKind::Start);
+        assert_eq!(grid.get_cell(one.0, one.1), CellKind::Goal);
+
+        let mut grid = BlockGrid::with_dims(3, 7);
+        grid.fill();
+        for column in 1..6 {
+            grid.set_cell(1, column, CellKind::Empty);
+        }
+        grid.set_start(Some((1, 1)));
+        grid.set_goal(Some((1, 5)));
+        grid.set_start(Some((1, 5)));
+        grid.set_goal(Some((1, 1)));
+        assert_eq!(grid.get_cell(1, 5), CellKind::Start);
+        assert_eq!(grid.get_cell(1, 1), CellKind::Goal);
+    }
+
+    #[test]
+    fn block_grid_skips_walls() {
+        let mut grid = BlockGrid::with_dims(3, 7);
+        grid.fill();
+        for column in 1..6 {
+            grid.set_cell(1, column, CellKind::Empty);
+        }
+
+        let diameter = find_diameter(&grid).unwrap();
+        assert_eq!(diameter.length, 4);
+        assert_eq!(diameter.ends, ((1, 5), (1, 1)));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::analysis::distances_from;
+use crate::grids::{CellKind, Grid};
+
+// evenly spaced colour stops, sampled with linear interpolation
+#[derive(Debug, Clone)]
+pub struct Gradient {
+    pub stops: Vec<[f32; 4]>,
+}
+
+impl Gradient {
+    pub fn new(stops: Vec<[f32; 4]>) -> Self {
+        Self { stops }
+    }
+
+    // t is clamped to [0, 1]
+    pub fn sample(&self, t: f32) -> [f32; 4] {
+        match self.stops.len() {
+            0 => [1.0, 1.0, 1.0, 1.0],
+            1 => self.stops[0],
+            len => {
+                let scaled = t.max(0.0).min(1.0) * (len - 1) as f32;
+                let low = (scaled as usize).min(len - 2);
+                let frac = scaled - low as f32;
+                let (from, to) = (self.stops[low], self.stops[low + 1]);
+
+                [
+                    from[0] + (to[0] - from[0]) * frac,
+                    from[1] + (to[1] - from[1]) * frac,
+                    from[2] + (to[2] - from[2]) * frac,
+                    from[3] + (to[3] - from[3]) * frac,
+                ]
+            }
+        }
+    }
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Self::new(vec![
+            [0.1, 0.1, 0.5, 1.0],
+            [0.1, 0.7, 0.6, 1.0],
+            [1.0, 0.9, 0.2, 1.0],
+        ])
+    }
+}
+
+// per cell colours by distance from start, None keeps the palette colour. walls, start, goal
+// and unreachable cells are left alone
+pub fn heatmap(grid: &dyn Grid, gradient: &Gradient) -> Option<Vec<Option<[f32; 4]>>> {
+    let start = grid.start()?;
+    let map = distances_from(grid, start);
+    let max = map
+        .distances
+        .iter()
+        .filter_map(|d| *d)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    Some(
+        grid.cells()
+            .iter()
+            .zip(map.distances.iter())
+            .map(|(cell, distance)| match (cell, distance) {
+                (CellKind::Wall, _) | (CellKind::Start, _) | (CellKind::Goal, _) => None,
+                (_, Some(distance)) => Some(gradient.sample(*distance as f32 / max as f32)),
+                (_, None) => None,
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod test_heatmap {
+    use super::*;
+    use crate::grids::block_grid::BlockGrid;
+
+    const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+    const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+    #[test]
+    fn gradient_blends_between_stops() {
+        let gradient = Gradient::new(vec![BLACK, WHITE, BLACK]);
+        assert_eq!(gradient.sample(0.0), BLACK);
+        assert_eq!(gradient.sample(0.5), WHITE);
+        assert_eq!(gradient.sample(1.0), BLACK);
+        // halfway between the first two stops
+        assert_eq!(gradient.sample(0.25), [0.5, 0.5, 0.5, 1.0]);
+        // out of range is clamped onto the ends
+        assert_eq!(gradient.sample(-1.0), BLACK);
+        assert_eq!(gradient.sample(2.0), BLACK);
+
+        let single = Gradient::new(vec![WHITE]);
+        assert_eq!(single.sample(0.0), WHITE);
+        assert_eq!(single.sample(0.7), WHITE);
+    }
+
+    #[test]
+    fn only_reachable_open_cells_are_coloured() {
+        // start, two open cells, a wall, then a cell cut off behind it
+        let mut grid = BlockGrid::with_dims(1, 5);
+        grid.set_cell(0, 3, CellKind::Wall);
+        let gradient = Gradient::new(vec![BLACK, WHITE]);
+        assert!(heatmap(&grid, &gradient).is_none());
+
+        grid.set_start(Some((0, 0)));
+        let colors = heatmap(&grid, &gradient).unwrap();
+        assert_eq!(
+            colors,
+            vec![None, Some([0.5, 0.5, 0.5, 1.0]), Some(WHITE), None, None]
+        );
+    }
+}
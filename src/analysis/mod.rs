@@ -0,0 +1,95 @@
+pub mod diameter;
+pub mod heatmap;
+pub mod stats;
+pub mod tour;
+pub mod validity;
+
+use crate::grids::{Dimensions, Grid};
+use std::collections::VecDeque;
+
+// cells a solver may step to from (row, column)
+pub fn open_neighbors_of(grid: &dyn Grid, row: usize, column: usize) -> Vec<(usize, usize)> {
+    grid.get_neighborhood_of(row, column)
+        .map(|(neighbor, _dir)| neighbor.1)
+        .filter(|coords| grid.is_passable((row, column), *coords))
+        .collect()
+}
+
+// breadth first distances from a single cell, unreachable cells are None
+pub struct DistanceMap {
+    pub dims: Dimensions,
+    pub root: (usize, usize),
+    pub distances: Vec<Option<usize>>,
+    parents: Vec<Option<usize>>,
+}
+
+impl DistanceMap {
+    pub fn distance_to(&self, row: usize, column: usize) -> Option<usize> {
+        self.distances[(row * self.dims.columns) + column]
+    }
+
+    // cells from root to (row, column), both ends included
+    pub fn path_to(&self, row: usize, column: usize) -> Option<Vec<(usize, usize)>> {
+        let mut index = (row * self.dims.columns) + column;
+        self.distances[index]?;
+
+        let mut path = vec![(row, column)];
+        while let Some(parent) = self.parents[index] {
+            index = parent;
+            path.push((index / self.dims.columns, index % self.dims.columns));
+        }
+        path.reverse();
+
+        Some(path)
+    }
+
+    // farthest reachable cell and its distance, ties go to the lowest index
+    pub fn farthest(&self) -> ((usize, usize), usize) {
+        let root = (self.root.0 * self.dims.columns) + self.root.1;
+        let (index, distance) = self
+            .distances
+            .iter()
+            .enumerate()
+            .filter_map(|(index, distance)| distance.map(|distance| (index, distance)))
+            .fold(
+                (root, 0),
+                |best, next| if next.1 > best.1 { next } else { best },
+            );
+
+        (
+            (index / self.dims.columns, index % self.dims.columns),
+            distance,
+        )
+    }
+}
+
+pub fn distances_from(grid: &dyn Grid, root: (usize, usize)) -> DistanceMap {
+    let dims = grid.dims();
+    let mut distances = vec![None; dims.rows * dims.columns];
+    let mut parents = vec![None; dims.rows * dims.columns];
+    let mut queue = VecDeque::new();
+
+    distances[(root.0 * dims.columns) + root.1] = Some(0);
+    queue.push_back(root);
+
+    while let Some((row, column)) = queue.pop_front() {
+        let index = (row * dims.columns) + column;
+        let distance = distances[index].unwrap();
+
+        for (n_row, n_col) in open_neighbors_of(grid, row, column) {
+            let n_index = (n_row * dims.columns) + n_col;
+            if distances[n_index].is_none() {
+                distances[n_index] = Some(distance + 1);
+                parents[n_index] = Some(index);
+                queue.push_back((n_row, n_col));
+            }
+        }
+    }
+
+    DistanceMap {
+        dims,
+        root,
+        distances,
+        parents,
+    }
+}
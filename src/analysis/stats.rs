@@ -0,0 +1,156 @@
+use crate::analysis::{distances_from, open_neighbors_of};
+use crate::grids::{CellKind, Grid};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct MazeStats {
+    pub open_cells: usize,
+    // cells with a single exit
+    pub dead_ends: usize,
+    pub dead_end_percent: f32,
+    // cells with three or more exits
+    pub junctions: usize,
+    // extra ways out summed over every junction, a 4-way crossing counts as 2
+    pub branches: usize,
+    // corridor length -> how many corridors, a corridor is a run of two exit cells
+    pub corridor_lengths: BTreeMap<usize, usize>,
+    pub longest_corridor: usize,
+    // mean cells walked from a dead end before hitting a junction, high values mean few long
+    // rivers rather than many short stubs
+    pub river_factor: f32,
+    // steps from start to goal
+    pub solution_length: Option<usize>,
+    // solution length over the manhattan distance between start and goal
+    pub solution_ratio: Option<f32>,
+}
+
+impl MazeStats {
+    pub fn from_grid(grid: &dyn Grid) -> Self {
+        let dims = grid.dims();
+        let exits: Vec<Vec<(usize, usize)>> = (0..dims.rows * dims.columns)
+            .map(|index| {
+                let (row, column) = (index / dims.columns, index % dims.columns);
+                if grid.get_cell(row, column) == CellKind::Wall {
+                    Vec::new()
+                } else {
+                    open_neighbors_of(grid, row, column)
+                }
+            })
+            .collect();
+        let index_of = |(row, column): (usize, usize)| (row * dims.columns) + column;
+
+        let mut stats = MazeStats::default();
+        stats.open_cells = grid
+            .cells()
+            .iter()
+            .filter(|cell| **cell != CellKind::Wall)
+            .count();
+
+        for cell_exits in &exits {
+            match cell_exits.len() {
+                1 => stats.dead_ends += 1,
+                n if n >= 3 => {
+                    stats.junctions += 1;
+                    stats.branches += n - 2;
+                }
+                _ => (),
+            }
+        }
+        if stats.open_cells > 0 {
+            stats.dead_end_percent = stats.dead_ends as f32 / stats.open_cells as f32 * 100.0;
+        }
+
+        let mut visited = vec![false; exits.len()];
+        for index in 0..exits.len() {
+            if visited[index] || exits[index].len() != 2 {
+                continue;
+            }
+
+            // flood both ways along the corridor
+            let mut length = 0;
+            let mut stack = vec![index];
+            visited[index] = true;
+            while let Some(current) = stack.pop() {
+                length += 1;
+                for next in exits[current].iter().map(|coords| index_of(*coords)) {
+                    if !visited[next] && exits[next].len() == 2 {
+                        visited[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+
+            *stats.corridor_lengths.entry(length).or_insert(0) += 1;
+            stats.longest_corridor = stats.longest_corridor.max(length);
+        }
+
+        let mut river_total = 0;
+        for index in 0..exits.len() {
+            if exits[index].len() != 1 {
+                continue;
+            }
+
+            let mut prev = index;
+            let mut current = index_of(exits[index][0]);
+            let mut length = 1;
+            while exits[current].len() == 2 {
+                let next = exits[current]
+                    .iter()
+                    .map(|coords| index_of(*coords))
+                    .find(|next| *next != prev)
+                    .unwrap();
+                prev = current;
+                current = next;
+                length += 1;
+            }
+            river_total += length;
+        }
+        if stats.dead_ends > 0 {
+            stats.river_factor = river_total as f32 / stats.dead_ends as f32;
+        }
+
+        if let (Some(start), Some(goal)) = (grid.start(), grid.goals().first()) {
+            stats.solution_length = distances_from(grid, start).distance_to(goal.0, goal.1);
+
+            let manhattan = (start.0 as isize - goal.0 as isize).abs()
+                + (start.1 as isize - goal.1 as isize).abs();
+            if manhattan > 0 {
+                stats.solution_ratio = stats
+                    .solution_length
+                    .map(|length| length as f32 / manhattan as f32);
+            }
+        }
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod test_stats {
+    use super::*;
+    use crate::grids::wall_grid::WallGrid;
+
+    #[test]
+    fn t_shaped_maze() {
+        // row 0 is a corridor, (1, 1) hangs off its middle
+        let mut grid = WallGrid::with_dims(2, 3);
+        grid.clear_wall_between((0, 0), (0, 1)).unwrap();
+        grid.clear_wall_between((0, 1), (0, 2)).unwrap();
+        grid.clear_wall_between((0, 1), (1, 1)).unwrap();
+        grid.clear_wall_between((1, 0), (1, 1)).unwrap();
+        grid.toggle_cell(0, 0, CellKind::Start);
+        grid.toggle_cell(1, 0, CellKind::Goal);
+
+        let stats = MazeStats::from_grid(&grid);
+        assert_eq!(stats.open_cells, 6);
+        // (1, 2) is walled in on every side and counts as neither
+        assert_eq!(stats.dead_ends, 3);
+        assert_eq!(stats.junctions, 1);
+        assert_eq!(stats.branches, 1);
+        assert_eq!(stats.longest_corridor, 1);
+        assert_eq!(stats.corridor_lengths.get(&1), Some(&1));
+        assert_eq!(stats.river_factor, 4.0 / 3.0);
+        assert_eq!(stats.solution_length, Some(3));
+        assert_eq!(stats.solution_ratio, Some(3.0));
+    }
+}
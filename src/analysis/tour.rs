@@ -0,0 +1,237 @@
+use crate::analysis::{distances_from, DistanceMap};
+use crate::grids::{CellKind, Grid};
+
+// held-karp is 2^n * n^2, past this we fall back to nearest neighbor + 2-opt
+pub const EXACT_GOAL_LIMIT: usize = 12;
+
+#[derive(Debug, Clone)]
+pub struct Tour {
+    // goals in the order they are visited
+    pub order: Vec<(usize, usize)>,
+    // every cell walked from start through the last goal, both ends included
+    pub path: Vec<(usize, usize)>,
+    pub length: usize,
+}
+
+// shortest walk from start that visits every goal, None if a goal is unreachable
+pub fn solve_tour(grid: &dyn Grid) -> Option<Tour> {
+    let start = grid.start()?;
+    let goals = grid.goals();
+    if goals.is_empty() {
+        return None;
+    }
+
+    // node 0 is the start, node i is goals[i - 1]
+    let mut maps = vec![distances_from(grid, start)];
+    maps.extend(goals.iter().map(|goal| distances_from(grid, *goal)));
+
+    let nodes: Vec<(usize, usize)> = std::iter::once(start)
+        .chain(goals.iter().cloned())
+        .collect();
+    let mut costs = vec![vec![0; nodes.len()]; nodes.len()];
+    for (from, map) in maps.iter().enumerate() {
+        for (to, node) in nodes.iter().enumerate() {
+            costs[from][to] = map.distance_to(node.0, node.1)?;
+        }
+    }
+
+    let order = if goals.len() <= EXACT_GOAL_LIMIT {
+        exact_order(&costs)
+    } else {
+        heuristic_order(&costs)
+    };
+
+    let length = order_cost(&costs, &order);
+    let path = stitch_path(&maps, &nodes, &order);
+
+    Some(Tour {
+        order: order.iter().map(|node| nodes[*node]).collect(),
+        path,
+        length,
+    })
+}
+
+// paints the tour onto the grid, leaving start and goals alone
+pub fn mark_tour(grid: &mut dyn Grid, tour: &Tour) {
+    let mut cells = grid.cells().clone();
+    cells.iter_mut().for_each(|cell| {
+        if *cell == CellKind::Path || *cell == CellKind::Explored || *cell == CellKind::Cursor {
+            *cell = CellKind::Empty;
+        }
+    });
+    grid.set_cells(cells);
+
+    for (row, column) in &tour.path {
+        if grid.get_cell(*row, *column) == CellKind::Empty {
+            grid.set_cell(*row, *column, CellKind::Path);
+        }
+    }
+}
+
+fn order_cost(costs: &[Vec<usize>], order: &[usize]) -> usize {
+    let mut prev = 0;
+    let mut total = 0;
+    for node in order {
+        total += costs[prev][*node];
+        prev = *node;
+    }
+
+    total
+}
+
+fn stitch_path(
+    maps: &[DistanceMap],
+    nodes: &[(usize, usize)],
+    order: &[usize],
+) -> Vec<(usize, usize)> {
+    let mut path = vec![nodes[0]];
+    let mut prev = 0;
+    for node in order {
+        let leg = maps[prev].path_to(nodes[*node].0, nodes[*node].1).unwrap();
+        // leg starts on the cell we are already standing on
+        path.extend_from_slice(&leg[1..]);
+        prev = *node;
+    }
+
+    path
+}
+
+// held-karp over an open path starting at node 0
+fn exact_order(costs: &[Vec<usize>]) -> Vec<usize> {
+    let goals = costs.len() - 1;
+    let full = (1 << goals) - 1;
+    let mut best = vec![vec![usize::MAX; goals]; 1 << goals];
+    let mut parent = vec![vec![usize::MAX; goals]; 1 << goals];
+
+    for last in 0..goals {
+        best[1 << last][last] = costs[0][last + 1];
+    }
+
+    for mask in 1..=full {
+        for last in 0..goals {
+            let cost = best[mask][last];
+            if mask & (1 << last) == 0 || cost == usize::MAX {
+                continue;
+            }
+
+            for next in 0..goals {
+                if mask & (1 << next) != 0 {
+                    continue;
+                }
+
+                let next_mask = mask | (1 << next);
+                let next_cost = cost + costs[last + 1][next + 1];
+                if next_cost < best[next_mask][next] {
+                    best[next_mask][next] = next_cost;
+                    parent[next_mask][next] = last;
+                }
+            }
+        }
+    }
+
+    let mut last = (0..goals).min_by_key(|last| best[full][*last]).unwrap();
+    let mut mask = full;
+    let mut order = Vec::with_capacity(goals);
+    loop {
+        order.push(last + 1);
+        let prev = parent[mask][last];
+        mask &= !(1 << last);
+        if mask == 0 {
+            break;
+        }
+        last = prev;
+    }
+    order.reverse();
+
+    order
+}
+
+// greedy nearest neighbor walk polished with 2-opt segment reversals
+fn heuristic_order(costs: &[Vec<usize>]) -> Vec<usize> {
+    let mut remaining: Vec<usize> = (1..costs.len()).collect();
+    let mut order = Vec::with_capacity(remaining.len());
+    let mut prev = 0;
+    while !remaining.is_empty() {
+        let (position, _) = remaining
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, node)| costs[prev][**node])
+            .unwrap();
+        prev = remaining.remove(position);
+        order.push(prev);
+    }
+
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..order.len() - 1 {
+            for j in i + 1..order.len() {
+                let before = if i == 0 { 0 } else { order[i - 1] };
+                let (first, last) = (order[i], order[j]);
+
+                let mut old_cost = costs[before][first];
+                let mut new_cost = costs[before][last];
+                if let Some(after) = order.get(j + 1) {
+                    old_cost += costs[last][*after];
+                    new_cost += costs[first][*after];
+                }
+
+                if new_cost < old_cost {
+                    order[i..=j].reverse();
+                    improved = true;
+                }
+            }
+        }
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod test_tour {
+    use super::*;
+    use crate::grids::wall_grid::WallGrid;
+
+    // a single corridor along row 0, goals scattered out of order
+    fn corridor(goals: &[usize]) -> WallGrid {
+        let mut grid = WallGrid::with_dims(1, 20);
+        for column in 0..19 {
+            grid.clear_wall_between((0, column), (0, column + 1))
+                .unwrap();
+        }
+        grid.set_multi_goal(true);
+        grid.toggle_cell(0, 0, CellKind::Start);
+        for goal in goals {
+            grid.toggle_cell(0, *goal, CellKind::Goal);
+        }
+
+        grid
+    }
+
+    #[test]
+    fn exact_visits_in_corridor_order() {
+        let grid = corridor(&[15, 3, 9]);
+        let tour = solve_tour(&grid).unwrap();
+
+        assert_eq!(tour.order, vec![(0, 3), (0, 9), (0, 15)]);
+        assert_eq!(tour.length, 15);
+        assert_eq!(tour.path.len(), 16);
+    }
+
+    #[test]
+    fn heuristic_matches_exact_on_corridor() {
+        let grid = corridor(&[19, 2, 17, 4, 15, 6, 13, 8, 11, 10, 9, 12, 7, 14]);
+        assert!(grid.goals().len() > EXACT_GOAL_LIMIT);
+
+        let tour = solve_tour(&grid).unwrap();
+        assert_eq!(tour.length, 19);
+    }
+
+    #[test]
+    fn unreachable_goal_has_no_tour() {
+        let mut grid = corridor(&[5]);
+        grid.add_wall_between((0, 2), (0, 3)).unwrap();
+
+        assert!(solve_tour(&grid).is_none());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::analysis::open_neighbors_of;
+use crate::grids::{CellKind, Grid};
+
+pub const REGION_COLOR: [f32; 4] = [1.0, 0.5, 0.0, 1.0];
+pub const LOOP_COLOR: [f32; 4] = [0.8, 0.0, 0.8, 1.0];
+
+#[derive(Debug, Clone)]
+pub struct Validation {
+    // connected groups of open cells, largest first
+    pub regions: Vec<Vec<(usize, usize)>>,
+    // one passage per independent loop, removing all of them leaves a spanning forest
+    pub loop_edges: Vec<((usize, usize), (usize, usize))>,
+    // None when start or goal is missing
+    pub solvable: Option<bool>,
+}
+
+impl Validation {
+    pub fn is_connected(&self) -> bool {
+        self.regions.len() <= 1
+    }
+
+    // fully connected with no loops
+    pub fn is_perfect(&self) -> bool {
+        self.is_connected() && self.loop_edges.is_empty()
+    }
+
+    // highlights every cell outside the largest region and both sides of each loop edge
+    pub fn overlay(&self, cell_count: usize, columns: usize) -> Vec<Option<[f32; 4]>> {
+        let mut overlay = vec![None; cell_count];
+        for region in self.regions.iter().skip(1) {
+            for (row, column) in region {
+                overlay[(row * columns) + column] = Some(REGION_COLOR);
+            }
+        }
+        for (one, two) in &self.loop_edges {
+            overlay[(one.0 * columns) + one.1] = Some(LOOP_COLOR);
+            overlay[(two.0 * columns) + two.1] = Some(LOOP_COLOR);
+        }
+
+        overlay
+    }
+}
+
+// works off passability, so WallGrid edges and BlockGrid open cells are treated the same
+pub fn validate(grid: &dyn Grid) -> Validation {
+    let dims = grid.dims();
+    let count = dims.rows * dims.columns;
+    let is_open = |index: usize| grid.cells()[index] != CellKind::Wall;
+
+    // union find over open cells, any edge joining an already joined pair closes a loop
+    let mut parents: Vec<usize> = (0..count).collect();
+    fn find(parents: &mut Vec<usize>, index: usize) -> usize {
+        let mut root = index;
+        while parents[root] != root {
+            root = parents[root];
+        }
+        let mut current = index;
+        while parents[current] != root {
+            let next = parents[current];
+            parents[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    let mut loop_edges = Vec::new();
+    for index in (0..count).filter(|index| is_open(*index)) {
+        let coords = (index / dims.columns, index % dims.columns);
+        for neighbor in open_neighbors_of(grid, coords.0, coords.1) {
+            let n_index = (neighbor.0 * dims.columns) + neighbor.1;
+            // every undirected edge once
+            if n_index < index || !is_open(n_index) {
+                continue;
+            }
+
+            let (root, n_root) = (find(&mut parents, index), find(&mut parents, n_index));
+            if root == n_root {
+                loop_edges.push((coords, neighbor));
+            } else {
+                parents[n_root] = root;
+            }
+        }
+    }
+
+    let mut by_root: Vec<Vec<(usize, usize)>> = vec![Vec::new(); count];
+    for index in (0..count).filter(|index| is_open(*index)) {
+        let root = find(&mut parents, index);
+        by_root[root].push((index / dims.columns, index % dims.columns));
+    }
+    let mut regions: Vec<Vec<(usize, usize)>> = by_root
+        .into_iter()
+        .filter(|region| !region.is_empty())
+        .collect();
+    regions.sort_by(|one, two| two.len().cmp(&one.len()));
+
+    let solvable = match (grid.start(), grid.goals().first()) {
+        (Some(start), Some(goal)) => {
+            let start = find(&mut parents, (start.0 * dims.columns) + start.1);
+            let goal = find(&mut parents, (goal.0 * dims.columns) + goal.1);
+            Some(start == goal)
+        }
+        _ => None,
+    };
+
+    Validation {
+        regions,
+        loop_edges,
+        solvable,
+    }
+}
+
+#[cfg(test)]
+mod test_validity {
+    use super::*;
+    use crate::grids::block_grid::BlockGrid;
+    use crate::grids::wall_grid::WallGrid;
+
+    #[test]
+    fn wall_grid_loop_and_island() {
+        let mut grid = WallGrid::with_dims(2, 3);
+        // square loop in the first two columns, (0, 2) and (1, 2) joined on their own
+        grid.clear_wall_between((0, 0), (0, 1)).unwrap();
+        grid.clear_wall_between((0, 1), (1, 1)).unwrap();
+        grid.clear_wall_between((1, 1), (1, 0)).unwrap();
+        grid.clear_wall_between((1, 0), (0, 0)).unwrap();
+        grid.clear_wall_between((0, 2), (1, 2)).unwrap();
+
+        let validation = validate(&grid);
+        assert_eq!(validation.regions.len(), 2);
+        assert_eq!(validation.regions[1], vec![(0, 2), (1, 2)]);
+        assert_eq!(validation.loop_edges.len(), 1);
+        assert!(!validation.is_perfect());
+
+        grid.clear_wall_between((0, 1), (0, 2)).unwrap();
+        grid.add_wall_between((1, 1), (1, 0)).unwrap();
+        assert!(validate(&grid).is_perfect());
+    }
+
+    #[test]
+    fn block_grid_open_square_is_a_loop() {
+        let mut grid = BlockGrid::with_dims(4, 4);
+        grid.fill();
+        grid.set_cell(1, 1, CellKind::Empty);
+        grid.set_cell(1, 2, CellKind::Empty);
+        grid.set_cell(2, 1, CellKind::Empty);
+        grid.set_cell(2, 2, CellKind::Empty);
+
+        let validation = validate(&grid);
+        assert!(validation.is_connected());
+        assert_eq!(validation.loop_edges.len(), 1);
+    }
+}
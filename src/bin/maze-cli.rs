@@ -0,0 +1,15 @@
+// the headless commands on their own, builds without the gui feature
+use maze_rs::cli;
+
+fn main() {
+    env_logger::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::run(&args) {
+        Some(Ok(())) => {}
+        Some(Err(e)) => {
+            eprintln!("maze-cli: {}", e);
+            std::process::exit(1);
+        }
+        None => println!("{}", cli::USAGE),
+    }
+}
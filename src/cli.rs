@@ -0,0 +1,504 @@
+// headless commands, main hands the arguments over before winit, wgpu or imgui are touched.
+// mazes travel between commands as json documents, on stdin and stdout unless --in or --out
+// name files, so they can be piped:
+//
+//   maze-rs generate --rows 20 --cols 20 --seed 7 | maze-rs solve |
+//       maze-rs export --format png --out maze.png
+use crate::analysis::distances_from;
+use crate::analysis::stats::MazeStats;
+use crate::formats;
+use crate::formats::json::MazeDocument;
+use crate::generators::{generate_grid, new_generator, GeneratorKind};
+use crate::grids::{Grid, GridKind, SolverKind, MAX_DIMENSION};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+
+pub const USAGE: &str = "\
+usage: maze-rs [command] [--option value]...
+       maze-cli [command] [--option value]...
+
+with no command maze-rs opens the app window, maze-cli is the same commands without the gui
+
+commands:
+  generate  --kind wall|block --generator aldous-broder|prims --rows N --cols N --seed N
+  solve     --solver bfs|dfs|astar --start ROW,COL --goal ROW,COL
+  export    --format text|unicode|json|binary|png|svg|gif|pdf|obj|stl|dot [--contract]
+  stats
+  book      --count N --per-page N --rows N --cols N --kind K --generator G --seed N --title T
+
+every command reads a maze from --in or stdin except generate and book, and writes to --out or
+stdout. input can be json, binary or text";
+
+// None when there is no command and the app should start
+pub fn run(args: &[String]) -> Option<io::Result<()>> {
+    let (command, rest) = args.split_first()?;
+    let options = match Options::parse(rest) {
+        Ok(options) => options,
+        Err(e) => return Some(Err(e)),
+    };
+
+    Some(match command.as_str() {
+        "generate" => generate(&options),
+        "solve" => solve(&options),
+        "export" => export(&options),
+        "stats" => stats(&options),
+        "book" => book(&options),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(invalid_input(format!(
+            "unknown command {}\n\n{}",
+            command, USAGE
+        ))),
+    })
+}
+
+struct Options {
+    values: HashMap<String, String>,
+}
+
+impl Options {
+    // --name value pairs, a flag with no value reads as true
+    fn parse(args: &[String]) -> io::Result<Self> {
+        let mut values = HashMap::new();
+        let mut args = args.iter().peekable();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| invalid_input(format!("expected an --option, found {}", arg)))?;
+            let value = match args.peek() {
+                Some(value) if !value.starts_with("--") => args.next().unwrap().clone(),
+                _ => "true".to_string(),
+            };
+            values.insert(name.to_string(), value);
+        }
+
+        Ok(Self { values })
+    }
+
+    fn get<T: FromStr>(&self, name: &str) -> io::Result<Option<T>> {
+        match self.values.get(name) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| invalid_input(format!("bad value for --{}: {}", name, value))),
+            None => Ok(None),
+        }
+    }
+
+    fn get_or<T: FromStr>(&self, name: &str, default: T) -> io::Result<T> {
+        Ok(self.get(name)?.unwrap_or(default))
+    }
+
+    fn get_with<T>(&self, name: &str, parse: fn(&str) -> Option<T>) -> io::Result<Option<T>> {
+        match self.values.get(name) {
+            Some(value) => parse(value)
+                .map(Some)
+                .ok_or_else(|| invalid_input(format!("bad value for --{}: {}", name, value))),
+            None => Ok(None),
+        }
+    }
+}
+
+fn parse_grid_kind(name: &str) -> Option<GridKind> {
+    match name {
+        "wall" => Some(GridKind::Wall),
+        "block" => Some(GridKind::Block),
+        _ => None,
+    }
+}
+
+fn parse_generator_kind(name: &str) -> Option<GeneratorKind> {
+    match name {
+        "aldous-broder" => Some(GeneratorKind::AldousBroder),
+        "prims" => Some(GeneratorKind::RandPrims),
+        _ => None,
+    }
+}
+
+fn parse_solver_kind(name: &str) -> Option<SolverKind> {
+    match name {
+        "bfs" => Some(SolverKind::BFS),
+        "dfs" => Some(SolverKind::DFS),
+        "astar" => Some(SolverKind::AStar),
+        _ => None,
+    }
+}
+
+fn parse_coords(coords: &str) -> Option<(usize, usize)> {
+    let mut parts = coords.split(',');
+    let row = parts.next()?.trim().parse().ok()?;
+    let column = parts.next()?.trim().parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some((row, column))
+}
+
+fn invalid_input<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidInput, error)
+}
+
+// the same bound loaded files and the app's typed sizes get
+fn check_max_dims(rows: usize, columns: usize) -> io::Result<()> {
+    if rows > MAX_DIMENSION || columns > MAX_DIMENSION {
+        return Err(invalid_input(format!(
+            "mazes can have at most {} rows and columns",
+            MAX_DIMENSION
+        )));
+    }
+
+    Ok(())
+}
+
+// a maze read from input, and the generator and seed it came from if known
+struct Maze {
+    kind: GridKind,
+    grid: Box<dyn Grid>,
+    generated: Option<(GeneratorKind, u64)>,
+}
+
+fn read_maze(options: &Options) -> io::Result<Maze> {
+    let mut bytes = Vec::new();
+    match options.get::<String>("in")? {
+        Some(path) => bytes = std::fs::read(path)?,
+        None => {
+            io::stdin().read_to_end(&mut bytes)?;
+        }
+    }
+
+    if bytes.starts_with(formats::binary::MAGIC) {
+        let (kind, grid) = formats::binary::MazeReader::new(bytes.as_slice())
+            .read_grid()?
+            .ok_or_else(|| formats::invalid_data("no maze in input"))?;
+        return Ok(Maze {
+            kind,
+            grid,
+            generated: None,
+        });
+    }
+
+    let text = String::from_utf8(bytes).map_err(formats::invalid_data)?;
+    if text.trim_start().starts_with('{') {
+        let document = MazeDocument::read(text.as_bytes())?;
+        return Ok(Maze {
+            kind: document.grid_kind,
+            grid: document.to_grid()?,
+            generated: document
+                .generator
+                .and_then(|kind| document.seed.map(|seed| (kind, seed))),
+        });
+    }
+
+    let (kind, grid) = formats::text::parse(&text)?;
+    Ok(Maze {
+        kind,
+        grid,
+        generated: None,
+    })
+}
+
+fn write_output(options: &Options, bytes: &[u8]) -> io::Result<()> {
+    match options.get::<String>("out")? {
+        Some(path) => std::fs::write(path, bytes),
+        None => {
+            let stdout = io::stdout();
+            let mut stdout = stdout.lock();
+            stdout.write_all(bytes)?;
+            stdout.flush()
+        }
+    }
+}
+
+fn write_document(options: &Options, maze: &Maze) -> io::Result<()> {
+    let mut bytes = Vec::new();
+    MazeDocument::from_grid(maze.grid.as_ref(), maze.kind, maze.generated).write(&mut bytes)?;
+    bytes.push(b'\n');
+    write_output(options, &bytes)
+}
+
+fn generate(options: &Options) -> io::Result<()> {
+    let kind = options
+        .get_with("kind", parse_grid_kind)?
+        .unwrap_or(GridKind::Wall);
+    let generator_kind = options
+        .get_with("generator", parse_generator_kind)?
+        .unwrap_or(GeneratorKind::AldousBroder);
+    let rows = options.get_or("rows", 16)?;
+    let columns = options.get_or("cols", 16)?;
+    if rows == 0 || columns == 0 {
+        return Err(invalid_input("mazes need at least one row and column"));
+    }
+    check_max_dims(rows, columns)?;
+    let seed = options.get_or("seed", rand::random())?;
+
+    let maze = Maze {
+        kind,
+        grid: generate_grid(generator_kind, rows, columns, kind, seed)?,
+        generated: Some((generator_kind, seed)),
+    };
+    write_document(options, &maze)
+}
+
+fn solve(options: &Options) -> io::Result<()> {
+    let mut maze = read_maze(options)?;
+    let dims = maze.grid.dims();
+    let solver_kind = options
+        .get_with("solver", parse_solver_kind)?
+        .unwrap_or(SolverKind::BFS);
+
+    let start = options
+        .get_with("start", parse_coords)?
+        .or_else(|| maze.grid.start())
+        .unwrap_or((0, 0));
+    let goal = match options
+        .get_with("goal", parse_coords)?
+        .or_else(|| maze.grid.goals().first().cloned())
+    {
+        Some(goal) => goal,
+        None => match (dims.rows.checked_sub(1), dims.columns.checked_sub(1)) {
+            (Some(row), Some(column)) => (row, column),
+            _ => return Err(invalid_input("the maze has no cells")),
+        },
+    };
+    for (name, (row, column)) in &[("start", start), ("goal", goal)] {
+        if *row >= dims.rows || *column >= dims.columns {
+            return Err(invalid_input(format!("{} is outside of the maze", name)));
+        }
+    }
+    if distances_from(maze.grid.as_ref(), start)
+        .distance_to(goal.0, goal.1)
+        .is_none()
+    {
+        return Err(invalid_input("the goal can not be reached from the start"));
+    }
+
+    maze.grid.set_multi_goal(false);
+    maze.grid.set_start(Some(start));
+    maze.grid.set_goal(Some(goal));
+    maze.grid.set_solver_kind(solver_kind);
+    maze.grid.solve_path()?;
+
+    write_document(options, &maze)
+}
+
+fn export(options: &Options) -> io::Result<()> {
+    let format: String = options.get_or("format", "text".to_string())?;
+    let maze = read_maze(options)?;
+    let grid = maze.grid.as_ref();
+    let raster = formats::raster::RasterOptions::default();
+
+    let mut bytes = Vec::new();
+    match format.as_str() {
+        "text" | "unicode" => {
+            let style = if format == "unicode" {
+                formats::text::WallStyle::Unicode
+            } else {
+                formats::text::WallStyle::Ascii
+            };
+            bytes = formats::text::to_text(grid, maze.kind, style).into_bytes();
+        }
+        "json" => {
+            MazeDocument::from_grid(grid, maze.kind, maze.generated).write(&mut bytes)?;
+            bytes.push(b'\n');
+        }
+        "binary" => {
+            let mut writer = formats::binary::MazeWriter::new(bytes);
+            writer.write_grid(grid, maze.kind)?;
+            bytes = writer.into_inner();
+        }
+        "png" => formats::raster::write_png(grid, maze.kind, &raster, &mut bytes)?,
+        "svg" => formats::svg::write_svg(grid, maze.kind, &raster, &mut bytes)?,
+        // replays the generator, so only mazes that remember their seed can be animated
+        "gif" => {
+            let (generator_kind, seed) = maze.generated.ok_or_else(|| {
+                invalid_input("gif export needs a maze that records its generator and seed")
+            })?;
+            let dims = grid.dims();
+            let mut generator =
+                new_generator(generator_kind, dims.rows, dims.columns, maze.kind, seed);
+            bytes = formats::animation::record_generation(
+                generator.as_mut(),
+                maze.kind,
+                formats::animation::AnimationOptions::default(),
+                bytes,
+            )?;
+        }
+        "pdf" => {
+            let mut page = formats::pdf::Page::default();
+            let margin = 48.0;
+            formats::pdf::draw_maze(
+                &mut page,
+                grid,
+                maze.kind,
+                [margin, margin],
+                [
+                    formats::pdf::PAGE_WIDTH - (2.0 * margin),
+                    formats::pdf::PAGE_HEIGHT - (2.0 * margin),
+                ],
+                true,
+            );
+            formats::pdf::PdfDocument { pages: vec![page] }.write(&mut bytes)?;
+        }
+        "obj" | "stl" => {
+            let mesh = formats::mesh::Mesh::from_grid(
+                grid,
+                maze.kind,
+                &formats::mesh::MeshOptions::default(),
+            );
+            if format == "obj" {
+                mesh.write_obj(&mut bytes)?;
+            } else {
+                mesh.write_stl(&mut bytes)?;
+            }
+        }
+        "dot" => {
+            let dot = formats::dot::DotOptions {
+                contract_corridors: options.get_or("contract", false)?,
+                ..formats::dot::DotOptions::default()
+            };
+            formats::dot::write_dot(grid, &dot, &mut bytes)?;
+        }
+        _ => return Err(invalid_input(format!("unknown format {}", format))),
+    }
+
+    write_output(options, &bytes)
+}
+
+fn stats(options: &Options) -> io::Result<()> {
+    let maze = read_maze(options)?;
+    let stats = MazeStats::from_grid(maze.grid.as_ref());
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "none".to_string());
+
+    let mut report = String::new();
+    report.push_str(&format!("open cells: {}\n", stats.open_cells));
+    report.push_str(&format!(
+        "dead ends: {} ({:.1}%)\n",
+        stats.dead_ends, stats.dead_end_percent
+    ));
+    report.push_str(&format!("junctions: {}\n", stats.junctions));
+    report.push_str(&format!("branches: {}\n", stats.branches));
+    report.push_str(&format!("longest corridor: {}\n", stats.longest_corridor));
+    report.push_str(&format!("river factor: {:.2}\n", stats.river_factor));
+    report.push_str(&format!(
+        "solution length: {}\n",
+        optional(stats.solution_length.map(|length| length.to_string()))
+    ));
+    report.push_str(&format!(
+        "solution ratio: {}\n",
+        optional(stats.solution_ratio.map(|ratio| format!("{:.2}", ratio)))
+    ));
+
+    write_output(options, report.as_bytes())
+}
+
+fn book(options: &Options) -> io::Result<()> {
+    let defaults = formats::pdf::BookOptions::default();
+    let book = formats::pdf::BookOptions {
+        title: options.get_or("title", defaults.title.clone())?,
+        count: options.get_or("count", defaults.count)?,
+        per_page: options.get_or("per-page", defaults.per_page)?,
+        rows: options.get_or("rows", defaults.rows)?,
+        columns: options.get_or("cols", defaults.columns)?,
+        grid_kind: options
+            .get_with("kind", parse_grid_kind)?
+            .unwrap_or(defaults.grid_kind),
+        generator_kind: options
+            .get_with("generator", parse_generator_kind)?
+            .unwrap_or(defaults.generator_kind),
+        seed: options.get_or("seed", defaults.seed)?,
+    };
+    if book.rows < 2 || book.columns < 2 {
+        return Err(invalid_input(
+            "book mazes need at least two rows and columns",
+        ));
+    }
+    check_max_dims(book.rows, book.columns)?;
+
+    let mut bytes = Vec::new();
+    formats::pdf::puzzle_book(&book)?.write(&mut bytes)?;
+    write_output(options, &bytes)
+}
+
+#[cfg(test)]
+mod test_cli {
+    use super::*;
+    use crate::grids::CellKind;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn options_pair_names_with_values() {
+        let options = Options::parse(&args("--rows 5 --contract --cols 7 --verbose")).unwrap();
+        assert_eq!(options.get::<usize>("rows").unwrap(), Some(5));
+        assert_eq!(options.get::<usize>("cols").unwrap(), Some(7));
+        // flags without a value, in the middle and at the end
+        assert_eq!(options.get::<bool>("contract").unwrap(), Some(true));
+        assert_eq!(options.get::<bool>("verbose").unwrap(), Some(true));
+        assert_eq!(options.get::<usize>("seed").unwrap(), None);
+        assert!(options.get::<usize>("contract").is_err());
+
+        let error = Options::parse(&args("--rows 5 6")).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn coords_are_two_numbers() {
+        assert_eq!(parse_coords("3,4"), Some((3, 4)));
+        assert_eq!(parse_coords(" 3 , 4 "), Some((3, 4)));
+        assert_eq!(parse_coords("3"), None);
+        assert_eq!(parse_coords("3,4,5"), None);
+        assert_eq!(parse_coords("3,x"), None);
+        assert_eq!(parse_coords("-1,4"), None);
+    }
+
+    #[test]
+    fn sizes_past_the_maximum_are_refused() {
+        let line = format!("generate --rows {} --cols 4", MAX_DIMENSION + 1);
+        let error = run(&args(&line)).unwrap().err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn generated_mazes_solve_through_files() {
+        let temp = |name: &str| {
+            let file = format!("maze-rs-test-{}-{}.json", std::process::id(), name);
+            std::env::temp_dir()
+                .join(file)
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+        let (generated, solved) = (&temp("generated"), &temp("solved"));
+
+        let line = format!("generate --rows 6 --cols 5 --seed 7 --out {}", generated);
+        run(&args(&line)).unwrap().unwrap();
+        let line = format!("solve --solver astar --in {} --out {}", generated, solved);
+        run(&args(&line)).unwrap().unwrap();
+
+        let read =
+            |path: &str| read_maze(&Options::parse(&args(&format!("--in {}", path))).unwrap());
+        let before = read(generated).unwrap();
+        let after = read(solved).unwrap();
+        std::fs::remove_file(generated).unwrap();
+        std::fs::remove_file(solved).unwrap();
+
+        // the solve keeps the walls, generator and seed, and only draws the path on
+        assert_eq!(before.kind, GridKind::Wall);
+        assert_eq!(after.generated, Some((GeneratorKind::AldousBroder, 7)));
+        let document =
+            |maze: &Maze| MazeDocument::from_grid(maze.grid.as_ref(), maze.kind, maze.generated);
+        assert_eq!(document(&before).passages, document(&after).passages);
+        assert_eq!(after.grid.start(), Some((0, 0)));
+        assert_eq!(after.grid.goals(), vec![(5, 4)]);
+        assert!(after.grid.cells().contains(&CellKind::Path));
+    }
+}
@@ -0,0 +1,39 @@
+// everything a grid or generator can refuse to do. the ui shows these instead of crashing and
+// the cli and file formats turn them into io errors
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MazeError {
+    NoStart,
+    NoGoal,
+    // walls and paths only exist between orthogonal neighbours
+    NotAdjacent((usize, usize), (usize, usize)),
+    OutOfBounds((usize, usize)),
+    Unsupported(&'static str),
+}
+
+pub type MazeResult<T> = Result<T, MazeError>;
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MazeError::NoStart => write!(f, "place a start first"),
+            MazeError::NoGoal => write!(f, "place a goal first"),
+            MazeError::NotAdjacent(one, two) => {
+                write!(f, "{:?} and {:?} are not neighbours", one, two)
+            }
+            MazeError::OutOfBounds(cell) => write!(f, "{:?} is outside the grid", cell),
+            MazeError::Unsupported(what) => write!(f, "{} is not supported here", what),
+        }
+    }
+}
+
+impl Error for MazeError {}
+
+impl From<MazeError> for io::Error {
+    fn from(error: MazeError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, error)
+    }
+}
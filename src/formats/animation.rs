@@ -0,0 +1,190 @@
+// animated gifs of generators and solvers at work, every frame drawn by the raster module so it
+// runs without a window. a recording with a fixed seed always comes out the same
+use crate::formats::raster::{rasterize, Canvas, RasterOptions};
+use crate::generators::Generator;
+use crate::grids::{Grid, GridKind};
+use std::io::{self, Write};
+
+#[derive(Debug, Clone)]
+pub struct AnimationOptions {
+    pub raster: RasterOptions,
+    // keep one frame out of every this many steps
+    pub every: usize,
+    // hundredths of a second per frame, and for the finished maze at the end
+    pub delay: u16,
+    pub hold: u16,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            raster: RasterOptions::default(),
+            every: 1,
+            delay: 4,
+            hold: 300,
+        }
+    }
+}
+
+pub struct GifRecorder<W: Write> {
+    encoder: gif::Encoder<W>,
+    options: AnimationOptions,
+    kind: GridKind,
+    steps: usize,
+    frames: usize,
+}
+
+impl<W: Write> GifRecorder<W> {
+    // the first frame is taken from grid right away and fixes the size of the animation
+    pub fn new(
+        writer: W,
+        grid: &dyn Grid,
+        kind: GridKind,
+        options: AnimationOptions,
+    ) -> io::Result<Self> {
+        let canvas = rasterize(grid, kind, &options.raster)?;
+        if canvas.width > u16::MAX as u32 || canvas.height > u16::MAX as u32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "maze too large for a gif, use a smaller cell size",
+            ));
+        }
+
+        let mut encoder = gif::Encoder::new(writer, canvas.width as u16, canvas.height as u16, &[])
+            .map_err(gif_error)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(gif_error)?;
+
+        let mut recorder = Self {
+            encoder,
+            options,
+            kind,
+            steps: 0,
+            frames: 0,
+        };
+        let delay = recorder.options.delay;
+        recorder.write_frame(canvas, delay)?;
+
+        Ok(recorder)
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    // call after every step, frames in between the kept ones are dropped
+    pub fn step(&mut self, grid: &dyn Grid) -> io::Result<()> {
+        self.steps += 1;
+        if self.steps % self.options.every.max(1) != 0 {
+            return Ok(());
+        }
+
+        let canvas = rasterize(grid, self.kind, &self.options.raster)?;
+        self.write_frame(canvas, self.options.delay)
+    }
+
+    pub fn finish(mut self, grid: &dyn Grid) -> io::Result<W> {
+        let canvas = rasterize(grid, self.kind, &self.options.raster)?;
+        let hold = self.options.hold;
+        self.write_frame(canvas, hold)?;
+
+        self.encoder.into_inner()
+    }
+
+    fn write_frame(&mut self, mut canvas: Canvas, delay: u16) -> io::Result<()> {
+        let (width, height) = (canvas.width as u16, canvas.height as u16);
+        let mut frame = match palette_of(&canvas) {
+            Some((palette, indices)) => {
+                gif::Frame::from_palette_pixels(width, height, &indices, &palette, None)
+            }
+            None => gif::Frame::from_rgba_speed(width, height, &mut canvas.pixels, 10),
+        };
+        frame.delay = delay;
+
+        self.frames += 1;
+        self.encoder.write_frame(&frame).map_err(gif_error)
+    }
+}
+
+// generates a whole maze, recording it as it goes
+pub fn record_generation<W: Write>(
+    generator: &mut dyn Generator,
+    kind: GridKind,
+    options: AnimationOptions,
+    writer: W,
+) -> io::Result<W> {
+    let mut recorder = GifRecorder::new(writer, generator.next_step()?, kind, options)?;
+    while !generator.is_done() {
+        recorder.step(generator.next_step()?)?;
+    }
+
+    recorder.finish(generator.generate_maze()?)
+}
+
+// solves grid from its start with its current solver, recording every step
+pub fn record_solve<W: Write>(
+    grid: &mut dyn Grid,
+    kind: GridKind,
+    options: AnimationOptions,
+    writer: W,
+) -> io::Result<W> {
+    grid.reset_solver()?;
+    let mut recorder = GifRecorder::new(writer, &*grid, kind, options)?;
+    while grid.step_solve_path(&mut Vec::new())? {
+        recorder.step(&*grid)?;
+    }
+
+    recorder.finish(&*grid)
+}
+
+// the raster only uses a handful of colors, so index them exactly and skip quantizing
+fn palette_of(canvas: &Canvas) -> Option<(Vec<u8>, Vec<u8>)> {
+    let mut colors: Vec<[u8; 3]> = Vec::new();
+    let mut indices = Vec::with_capacity(canvas.pixels.len() / 4);
+    for pixel in canvas.pixels.chunks(4) {
+        let color = [pixel[0], pixel[1], pixel[2]];
+        let index = match colors.iter().position(|known| *known == color) {
+            Some(index) => index,
+            None if colors.len() < 256 => {
+                colors.push(color);
+                colors.len() - 1
+            }
+            None => return None,
+        };
+        indices.push(index as u8);
+    }
+
+    Some((colors.concat(), indices))
+}
+
+fn gif_error(error: gif::EncodingError) -> io::Error {
+    match error {
+        gif::EncodingError::Io(error) => error,
+        error => io::Error::new(io::ErrorKind::Other, error),
+    }
+}
+
+#[cfg(test)]
+mod test_animation {
+    use super::*;
+    use crate::generators::new_generator;
+    use crate::generators::GeneratorKind;
+
+    #[test]
+    fn same_seed_same_gif() {
+        let record = |every: usize| {
+            let mut generator = new_generator(GeneratorKind::AldousBroder, 4, 4, GridKind::Wall, 3);
+            let options = AnimationOptions {
+                every,
+                ..AnimationOptions::default()
+            };
+            record_generation(generator.as_mut(), GridKind::Wall, options, Vec::new()).unwrap()
+        };
+
+        let gif = record(1);
+        assert_eq!(&gif[..6], b"GIF89a");
+        assert_eq!(gif, record(1));
+        assert!(record(5).len() < gif.len());
+    }
+}
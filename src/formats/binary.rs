@@ -0,0 +1,388 @@
+// compact binary mazes, any number of them back to back in one stream
+//
+// header, integers little endian:
+//   magic     4 bytes  b"MAZE"
+//   version   u8
+//   kind      u8       0 block, 1 wall
+//   flags     u8       HAS_START, HAS_GOALS
+//   reserved  u8
+//   rows      u32
+//   columns   u32
+//   start     u32 row, u32 column           if HAS_START
+//   goals     u32 count, then row/column    if HAS_GOALS
+//
+// body, packed least significant bit first:
+//   wall grids store 2 bits per cell, bit 0 is the east wall and bit 1 the south wall
+//   block grids store 1 bit per cell, set for walls
+//
+// only walls, start and goals survive, solver markings are dropped
+use crate::formats::{checked_cells, invalid_data};
+use crate::grids::block_grid::BlockGrid;
+use crate::grids::wall_grid::WallGrid;
+use crate::grids::{CellKind, Grid, GridKind};
+use std::io::{self, Read, Write};
+
+pub const MAGIC: &[u8; 4] = b"MAZE";
+pub const FORMAT_VERSION: u8 = 1;
+
+pub const HAS_START: u8 = 1 << 0;
+pub const HAS_GOALS: u8 = 1 << 1;
+
+const EAST_WALL: u8 = 1 << 0;
+const SOUTH_WALL: u8 = 1 << 1;
+
+pub struct MazeWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> MazeWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    pub fn write_grid(&mut self, grid: &dyn Grid, kind: GridKind) -> io::Result<()> {
+        let dims = grid.dims();
+        let start = grid.start();
+        let goals = grid.goals();
+
+        let mut flags = 0;
+        if start.is_some() {
+            flags |= HAS_START;
+        }
+        if !goals.is_empty() {
+            flags |= HAS_GOALS;
+        }
+
+        let kind_code = match kind {
+            GridKind::Block => 0,
+            GridKind::Wall => 1,
+        };
+        self.inner.write_all(MAGIC)?;
+        self.inner
+            .write_all(&[FORMAT_VERSION, kind_code, flags, 0])?;
+        write_u32(&mut self.inner, dims.rows)?;
+        write_u32(&mut self.inner, dims.columns)?;
+        if let Some(start) = start {
+            write_u32(&mut self.inner, start.0)?;
+            write_u32(&mut self.inner, start.1)?;
+        }
+        if !goals.is_empty() {
+            write_u32(&mut self.inner, goals.len())?;
+            for goal in goals {
+                write_u32(&mut self.inner, goal.0)?;
+                write_u32(&mut self.inner, goal.1)?;
+            }
+        }
+
+        let mut body = BitWriter::default();
+        for row in 0..dims.rows {
+            for column in 0..dims.columns {
+                match kind {
+                    GridKind::Block => {
+                        body.push_bits((grid.get_cell(row, column) == CellKind::Wall) as u8, 1)
+                    }
+                    GridKind::Wall => {
+                        let mut walls = 0;
+                        if column + 1 == dims.columns
+                            || !grid.is_passable((row, column), (row, column + 1))
+                        {
+                            walls |= EAST_WALL;
+                        }
+                        if row == 0 || !grid.is_passable((row, column), (row - 1, column)) {
+                            walls |= SOUTH_WALL;
+                        }
+                        body.push_bits(walls, 2);
+                    }
+                }
+            }
+        }
+        self.inner.write_all(&body.finish())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub struct MazeReader<R: Read> {
+    inner: R,
+}
+
+impl<R: Read> MazeReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    // Ok(None) once the stream ends cleanly between mazes
+    pub fn read_grid(&mut self) -> io::Result<Option<(GridKind, Box<dyn Grid>)>> {
+        let mut magic = [0; 4];
+        let mut filled = 0;
+        while filled < magic.len() {
+            match self.inner.read(&mut magic[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(read) => filled += read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if &magic != MAGIC {
+            return Err(invalid_data("not a maze, bad magic number"));
+        }
+
+        let mut header = [0; 4];
+        self.inner.read_exact(&mut header)?;
+        let [version, kind_code, flags, _reserved] = header;
+        if version > FORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "maze format version {} is newer than {}",
+                version, FORMAT_VERSION
+            )));
+        }
+        let kind = match kind_code {
+            0 => GridKind::Block,
+            1 => GridKind::Wall,
+            _ => return Err(invalid_data(format!("unknown grid kind {}", kind_code))),
+        };
+
+        let rows = read_u32(&mut self.inner)?;
+        let columns = read_u32(&mut self.inner)?;
+        let cell_count = checked_cells(rows, columns)?;
+        let read_coords = |inner: &mut R| -> io::Result<(usize, usize)> {
+            let coords = (read_u32(inner)?, read_u32(inner)?);
+            if coords.0 >= rows || coords.1 >= columns {
+                return Err(invalid_data("coordinates outside of the maze"));
+            }
+            Ok(coords)
+        };
+
+        let start = if flags & HAS_START != 0 {
+            Some(read_coords(&mut self.inner)?)
+        } else {
+            None
+        };
+        let mut goals = Vec::new();
+        if flags & HAS_GOALS != 0 {
+            let count = read_u32(&mut self.inner)?;
+            if count > cell_count {
+                return Err(invalid_data("more goals than cells"));
+            }
+            for _ in 0..count {
+                goals.push(read_coords(&mut self.inner)?);
+            }
+        }
+
+        let bits_per_cell = match kind {
+            GridKind::Block => 1,
+            GridKind::Wall => 2,
+        };
+        let body_bits = cell_count
+            .checked_mul(bits_per_cell)
+            .ok_or_else(|| invalid_data("maze is too large"))?;
+        let mut body = vec![0; (body_bits + 7) / 8];
+        self.inner.read_exact(&mut body)?;
+        let mut body = BitReader::new(&body);
+
+        let grid: Box<dyn Grid> = match kind {
+            GridKind::Block => {
+                let mut grid = BlockGrid::with_dims(rows, columns);
+                for index in 0..cell_count {
+                    if body.pull_bits(1) != 0 {
+                        grid.cells[index] = CellKind::Wall;
+                    }
+                }
+                mark_ends(&mut grid.cells, columns, start, &goals);
+                grid.start = start;
+                grid.goal = goals.first().cloned();
+                grid.multi_goal = goals.len() > 1;
+                grid.goals = goals;
+                Box::new(grid)
+            }
+            GridKind::Wall => {
+                let mut grid = WallGrid::with_dims(rows, columns);
+                for row in 0..rows {
+                    for column in 0..columns {
+                        let walls = body.pull_bits(2);
+                        if column + 1 < columns && walls & EAST_WALL == 0 {
+                            grid.clear_wall_between((row, column), (row, column + 1))?;
+                        }
+                        if row > 0 && walls & SOUTH_WALL == 0 {
+                            grid.clear_wall_between((row, column), (row - 1, column))?;
+                        }
+                    }
+                }
+                mark_ends(&mut grid.cells, columns, start, &goals);
+                grid.start = start;
+                grid.goal = goals.first().cloned();
+                grid.multi_goal = goals.len() > 1;
+                grid.goals = goals;
+                Box::new(grid)
+            }
+        };
+
+        Ok(Some((kind, grid)))
+    }
+}
+
+impl<R: Read> Iterator for MazeReader<R> {
+    type Item = io::Result<(GridKind, Box<dyn Grid>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_grid().transpose()
+    }
+}
+
+fn mark_ends(
+    cells: &mut [CellKind],
+    columns: usize,
+    start: Option<(usize, usize)>,
+    goals: &[(usize, usize)],
+) {
+    if let Some((row, column)) = start {
+        cells[(row * columns) + column] = CellKind::Start;
+    }
+    for (row, column) in goals {
+        cells[(row * columns) + column] = CellKind::Goal;
+    }
+}
+
+fn write_u32<W: Write>(writer: &mut W, value: usize) -> io::Result<()> {
+    if value > u32::MAX as usize {
+        return Err(invalid_data("maze too large for the binary format"));
+    }
+    writer.write_all(&(value as u32).to_le_bytes())
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<usize> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes) as usize)
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    used: u32,
+}
+
+impl BitWriter {
+    fn push_bits(&mut self, bits: u8, count: u32) {
+        for shift in 0..count {
+            if self.used % 8 == 0 {
+                self.bytes.push(0);
+            }
+            let bit = (bits >> shift) & 1;
+            *self.bytes.last_mut().unwrap() |= bit << (self.used % 8);
+            self.used += 1;
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    used: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, used: 0 }
+    }
+
+    fn pull_bits(&mut self, count: u32) -> u8 {
+        let mut bits = 0;
+        for shift in 0..count {
+            let bit = (self.bytes[self.used / 8] >> (self.used % 8)) & 1;
+            bits |= bit << shift;
+            self.used += 1;
+        }
+        bits
+    }
+}
+
+#[cfg(test)]
+mod test_binary {
+    use super::*;
+
+    #[test]
+    fn stream_round_trip() {
+        let mut wall = WallGrid::with_dims(3, 5);
+        wall.clear_wall_between((0, 0), (0, 1)).unwrap();
+        wall.clear_wall_between((1, 3), (2, 3)).unwrap();
+        wall.clear_wall_between((2, 4), (2, 3)).unwrap();
+        wall.toggle_cell(0, 0, CellKind::Start);
+        wall.toggle_cell(2, 4, CellKind::Goal);
+
+        let mut block = BlockGrid::with_dims(3, 3);
+        block.fill();
+        block.set_cell(1, 1, CellKind::Empty);
+
+        let mut writer = MazeWriter::new(Vec::new());
+        writer.write_grid(&wall, GridKind::Wall).unwrap();
+        writer.write_grid(&block, GridKind::Block).unwrap();
+        let bytes = writer.into_inner();
+        // 16 byte headers, the wall maze adds its ends and 30 bits of walls, the block maze 9 bits
+        assert_eq!(bytes.len(), (16 + 8 + 12 + 4) + (16 + 2));
+
+        let mazes: Vec<_> = MazeReader::new(bytes.as_slice())
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(mazes.len(), 2);
+
+        let (kind, read_wall) = &mazes[0];
+        assert_eq!(*kind, GridKind::Wall);
+        assert_eq!(read_wall.paths(), wall.paths());
+        assert_eq!(read_wall.cells(), wall.cells());
+        assert_eq!(read_wall.start(), Some((0, 0)));
+        assert_eq!(read_wall.goals(), vec![(2, 4)]);
+
+        let (kind, read_block) = &mazes[1];
+        assert_eq!(*kind, GridKind::Block);
+        assert_eq!(read_block.cells(), block.cells());
+    }
+
+    #[test]
+    fn hostile_headers_are_refused() {
+        let header = |rows: u32, columns: u32, goals: Option<u32>| {
+            let mut bytes = MAGIC.to_vec();
+            let flags = if goals.is_some() { HAS_GOALS } else { 0 };
+            bytes.extend_from_slice(&[FORMAT_VERSION, 1, flags, 0]);
+            bytes.extend_from_slice(&rows.to_le_bytes());
+            bytes.extend_from_slice(&columns.to_le_bytes());
+            if let Some(count) = goals {
+                bytes.extend_from_slice(&count.to_le_bytes());
+            }
+            bytes
+        };
+        let kind_of = |bytes: Vec<u8>| {
+            MazeReader::new(bytes.as_slice())
+                .read_grid()
+                .err()
+                .map(|e| e.kind())
+        };
+
+        assert_eq!(
+            kind_of(header(0, 0, None)),
+            Some(io::ErrorKind::InvalidData)
+        );
+        assert_eq!(
+            kind_of(header(u32::MAX, u32::MAX, None)),
+            Some(io::ErrorKind::InvalidData)
+        );
+        assert_eq!(
+            kind_of(header(3, 3, Some(u32::MAX))),
+            Some(io::ErrorKind::InvalidData)
+        );
+    }
+}
@@ -0,0 +1,198 @@
+// graphviz export of the graph solvers walk, the same open cell adjacency the wall grid graph
+// and BlockGrid::make_graph hold. positions are pinned so neato lays it out like the maze
+use crate::analysis::open_neighbors_of;
+use crate::analysis::tour::solve_tour;
+use crate::grids::{CellKind, Grid};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+pub const SOLUTION_COLOR: &str = "#1a801a";
+
+#[derive(Debug, Clone)]
+pub struct DotOptions {
+    // keep only junctions, dead ends, start and goals, corridors become weighted edges
+    pub contract_corridors: bool,
+    pub highlight_solution: bool,
+    // points between neighbouring cells
+    pub spacing: f32,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        Self {
+            contract_corridors: false,
+            highlight_solution: true,
+            spacing: 36.0,
+        }
+    }
+}
+
+type Cell = (usize, usize);
+
+pub fn to_dot(grid: &dyn Grid, options: &DotOptions) -> String {
+    let dims = grid.dims();
+    let is_open = |(row, column): Cell| grid.get_cell(row, column) != CellKind::Wall;
+    let neighbors = |(row, column): Cell| open_neighbors_of(grid, row, column);
+    let ends: HashSet<Cell> = grid.start().into_iter().chain(grid.goals()).collect();
+
+    // solution steps stored both ways round
+    let mut solution: HashSet<(Cell, Cell)> = HashSet::new();
+    if options.highlight_solution {
+        if let Some(tour) = solve_tour(grid) {
+            for step in tour.path.windows(2) {
+                solution.insert((step[0], step[1]));
+                solution.insert((step[1], step[0]));
+            }
+        }
+    }
+
+    let cells: Vec<Cell> = (0..dims.rows)
+        .flat_map(|row| (0..dims.columns).map(move |column| (row, column)))
+        .filter(|cell| is_open(*cell))
+        .collect();
+    let mut kept: HashSet<Cell> = cells
+        .iter()
+        .cloned()
+        .filter(|cell| {
+            !options.contract_corridors || ends.contains(cell) || neighbors(*cell).len() != 2
+        })
+        .collect();
+
+    // (from, to, steps, on the solution)
+    let mut edges = Vec::new();
+    if options.contract_corridors {
+        let mut walked: HashSet<(Cell, Cell)> = HashSet::new();
+        let mut visited: HashSet<Cell> = HashSet::new();
+        let mut pending: Vec<Cell> = cells.iter().cloned().filter(|c| kept.contains(c)).collect();
+
+        loop {
+            while let Some(from) = pending.pop() {
+                for first in neighbors(from) {
+                    if walked.contains(&(from, first)) {
+                        continue;
+                    }
+
+                    let (mut prev, mut at, mut steps) = (from, first, 1);
+                    let mut on_solution = solution.contains(&(from, first));
+                    while !kept.contains(&at) {
+                        visited.insert(at);
+                        let next = neighbors(at).into_iter().find(|n| *n != prev).unwrap();
+                        on_solution &= solution.contains(&(at, next));
+                        prev = at;
+                        at = next;
+                        steps += 1;
+                    }
+
+                    walked.insert((from, first));
+                    walked.insert((at, prev));
+                    edges.push((from.min(at), from.max(at), steps, on_solution));
+                }
+            }
+
+            // rings made only of corridor cells have nothing kept on them yet
+            match cells
+                .iter()
+                .find(|cell| !kept.contains(cell) && !visited.contains(cell))
+            {
+                Some(cell) => {
+                    kept.insert(*cell);
+                    pending.push(*cell);
+                }
+                None => break,
+            }
+        }
+    } else {
+        for cell in &cells {
+            for neighbor in neighbors(*cell) {
+                if *cell < neighbor {
+                    edges.push((*cell, neighbor, 1, solution.contains(&(*cell, neighbor))));
+                }
+            }
+        }
+    }
+
+    let name = |(row, column): Cell| format!("r{}c{}", row, column);
+    let mut dot = String::new();
+    dot.push_str("graph maze {\n");
+    dot.push_str("  graph [layout=neato];\n");
+    dot.push_str("  node [shape=circle, style=filled, fillcolor=white, fontsize=8];\n");
+
+    for cell in cells.iter().filter(|cell| kept.contains(cell)) {
+        let (x, y) = (
+            cell.1 as f32 * options.spacing / 72.0,
+            cell.0 as f32 * options.spacing / 72.0,
+        );
+        let mut attributes = format!(r#"label="{},{}", pos="{},{}!""#, cell.0, cell.1, x, y);
+        match grid.get_cell(cell.0, cell.1) {
+            CellKind::Start => attributes.push_str(r##", fillcolor="#ff0000""##),
+            CellKind::Goal => attributes.push_str(r##", fillcolor="#ffff00""##),
+            _ => {}
+        }
+        let _ = writeln!(dot, "  {} [{}];", name(*cell), attributes);
+    }
+
+    for (from, to, steps, on_solution) in edges {
+        let mut attributes = Vec::new();
+        if steps > 1 {
+            attributes.push(format!(r#"label="{}", len={}"#, steps, steps));
+        }
+        if on_solution {
+            attributes.push(format!(r#"color="{}", penwidth=3"#, SOLUTION_COLOR));
+        }
+        if attributes.is_empty() {
+            let _ = writeln!(dot, "  {} -- {};", name(from), name(to));
+        } else {
+            let _ = writeln!(
+                dot,
+                "  {} -- {} [{}];",
+                name(from),
+                name(to),
+                attributes.join(", ")
+            );
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+pub fn write_dot<W: Write>(grid: &dyn Grid, options: &DotOptions, mut writer: W) -> io::Result<()> {
+    writer.write_all(to_dot(grid, options).as_bytes())
+}
+
+#[cfg(test)]
+mod test_dot {
+    use super::*;
+    use crate::grids::wall_grid::WallGrid;
+
+    #[test]
+    fn corridors_contract_to_weighted_edges() {
+        // a corridor from the start up to the goal, then on around the top to a dead end
+        //   G . .
+        //   .   .
+        //   S
+        let mut grid = WallGrid::with_dims(3, 3);
+        grid.clear_wall_between((0, 0), (1, 0)).unwrap();
+        grid.clear_wall_between((1, 0), (2, 0)).unwrap();
+        grid.clear_wall_between((2, 0), (2, 1)).unwrap();
+        grid.clear_wall_between((2, 1), (2, 2)).unwrap();
+        grid.clear_wall_between((2, 2), (1, 2)).unwrap();
+        grid.toggle_cell(0, 0, CellKind::Start);
+        grid.toggle_cell(2, 0, CellKind::Goal);
+
+        let full = to_dot(&grid, &DotOptions::default());
+        assert_eq!(full.matches(" -- ").count(), 5);
+        assert_eq!(full.matches("penwidth").count(), 2);
+
+        let options = DotOptions {
+            contract_corridors: true,
+            ..DotOptions::default()
+        };
+        let contracted = to_dot(&grid, &options);
+        // start, goal and the dead end at (1, 2), plus the three cells nothing reaches
+        assert_eq!(contracted.matches("pos=").count(), 6);
+        assert!(contracted.contains(r#"r1c2 -- r2c0 [label="3", len=3];"#));
+        assert!(contracted.contains("r0c0 -- r2c0 [label=\"2\", len=2, color"));
+    }
+}
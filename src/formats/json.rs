@@ -0,0 +1,184 @@
+use crate::formats::{checked_cells, invalid_data};
+use crate::generators::GeneratorKind;
+use crate::grids::block_grid::BlockGrid;
+use crate::grids::wall_grid::WallGrid;
+use crate::grids::{CellKind, Dimensions, Grid, GridKind, SolverKind};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+
+// bump when a field changes meaning, readers refuse documents newer than they know
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MazeDocument {
+    pub version: u32,
+    pub grid_kind: GridKind,
+    pub dims: Dimensions,
+    pub cells: Vec<CellKind>,
+    // open wall grid edges, listed once each. block grids keep their walls in cells
+    pub passages: Vec<((usize, usize), (usize, usize))>,
+    pub start: Option<(usize, usize)>,
+    pub goals: Vec<(usize, usize)>,
+    pub solver_kind: SolverKind,
+    pub generator: Option<GeneratorKind>,
+    pub seed: Option<u64>,
+}
+
+impl MazeDocument {
+    // generated is the generator and seed the maze came from, if it came from one
+    pub fn from_grid(
+        grid: &dyn Grid,
+        grid_kind: GridKind,
+        generated: Option<(GeneratorKind, u64)>,
+    ) -> Self {
+        let dims = grid.dims();
+        let mut passages = Vec::new();
+        if grid_kind == GridKind::Wall {
+            for row in 0..dims.rows {
+                for column in 0..dims.columns {
+                    if column + 1 < dims.columns
+                        && grid.is_passable((row, column), (row, column + 1))
+                    {
+                        passages.push(((row, column), (row, column + 1)));
+                    }
+                    if row + 1 < dims.rows && grid.is_passable((row, column), (row + 1, column)) {
+                        passages.push(((row, column), (row + 1, column)));
+                    }
+                }
+            }
+        }
+
+        Self {
+            version: FORMAT_VERSION,
+            grid_kind,
+            dims,
+            cells: grid.cells().clone(),
+            passages,
+            start: grid.start(),
+            goals: grid.goals(),
+            solver_kind: grid.solver_kind(),
+            generator: generated.map(|(kind, _)| kind),
+            seed: generated.map(|(_, seed)| seed),
+        }
+    }
+
+    pub fn to_grid(&self) -> io::Result<Box<dyn Grid>> {
+        let Dimensions { rows, columns } = self.dims;
+        let cell_count = checked_cells(rows, columns)?;
+        if self.cells.len() != cell_count {
+            return Err(invalid_data(format!(
+                "expected {} cells for {}x{}, found {}",
+                cell_count,
+                rows,
+                columns,
+                self.cells.len()
+            )));
+        }
+        let in_bounds = |(row, column): (usize, usize)| row < rows && column < columns;
+        let ends_in_bounds = self
+            .start
+            .iter()
+            .chain(self.goals.iter())
+            .all(|c| in_bounds(*c));
+        let passages_in_bounds = self
+            .passages
+            .iter()
+            .all(|(one, two)| in_bounds(*one) && in_bounds(*two));
+        if !ends_in_bounds || !passages_in_bounds {
+            return Err(invalid_data("coordinates outside of the maze"));
+        }
+
+        let grid: Box<dyn Grid> = match self.grid_kind {
+            GridKind::Block => {
+                let mut grid = BlockGrid::with_dims(rows, columns);
+                grid.cells = self.cells.clone();
+                grid.start = self.start;
+                grid.goals = self.goals.clone();
+                grid.goal = self.goals.first().cloned();
+                grid.multi_goal = self.goals.len() > 1;
+                grid.solver_kind = self.solver_kind;
+                Box::new(grid)
+            }
+            GridKind::Wall => {
+                let mut grid = WallGrid::with_dims(rows, columns);
+                grid.cells = self.cells.clone();
+                grid.start = self.start;
+                grid.goals = self.goals.clone();
+                grid.goal = self.goals.first().cloned();
+                grid.multi_goal = self.goals.len() > 1;
+                grid.solver_kind = self.solver_kind;
+                for (one, two) in &self.passages {
+                    grid.clear_wall_between(*one, *two).map_err(invalid_data)?;
+                }
+                Box::new(grid)
+            }
+        };
+
+        Ok(grid)
+    }
+
+    pub fn write<W: Write>(&self, writer: W) -> io::Result<()> {
+        serde_json::to_writer_pretty(writer, self).map_err(io::Error::from)
+    }
+
+    pub fn read<R: Read>(reader: R) -> io::Result<Self> {
+        let document: Self = serde_json::from_reader(reader).map_err(io::Error::from)?;
+        if document.version > FORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "maze format version {} is newer than {}",
+                document.version, FORMAT_VERSION
+            )));
+        }
+
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+mod test_json {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut grid = WallGrid::with_dims(2, 3);
+        grid.clear_wall_between((0, 0), (0, 1)).unwrap();
+        grid.clear_wall_between((0, 1), (1, 1)).unwrap();
+        grid.toggle_cell(0, 0, CellKind::Start);
+        grid.toggle_cell(1, 1, CellKind::Goal);
+
+        let document =
+            MazeDocument::from_grid(&grid, GridKind::Wall, Some((GeneratorKind::RandPrims, 7)));
+        let mut bytes = Vec::new();
+        document.write(&mut bytes).unwrap();
+
+        let read = MazeDocument::read(bytes.as_slice()).unwrap();
+        assert_eq!(read.seed, Some(7));
+        assert_eq!(read.generator, Some(GeneratorKind::RandPrims));
+
+        let loaded = read.to_grid().unwrap();
+        assert_eq!(loaded.cells(), grid.cells());
+        assert_eq!(loaded.paths(), grid.paths());
+        assert_eq!(loaded.start(), Some((0, 0)));
+        assert_eq!(loaded.goals(), vec![(1, 1)]);
+    }
+
+    #[test]
+    fn empty_and_oversized_mazes_are_refused() {
+        let mut document =
+            MazeDocument::from_grid(&WallGrid::with_dims(2, 3), GridKind::Wall, None);
+        document.dims = Dimensions {
+            rows: 0,
+            columns: 0,
+        };
+        document.cells.clear();
+        assert!(document.to_grid().is_err());
+
+        // would wrap around to a handful of cells if multiplied unchecked
+        document.dims = Dimensions {
+            rows: usize::MAX / 2 + 1,
+            columns: 2,
+        };
+        let error = document.to_grid().err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,398 @@
+// 3d meshes for printing and game engines. the maze becomes a lattice of solid and open blocks,
+// a wall grid lattice puts posts and walls on the even lines with cells between them, so every
+// wall is a single block shared by the cells on both sides. the lattice is extruded as one solid
+// over a floor plate, with x along columns, y along rows and z up
+use crate::grids::{CellKind, Grid, GridKind};
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+#[derive(Debug, Clone)]
+pub struct MeshOptions {
+    // across an open cell, block grids use it for every block
+    pub cell_size: f32,
+    // across a wall grid wall
+    pub wall_thickness: f32,
+    pub wall_height: f32,
+    pub floor_thickness: f32,
+}
+
+impl Default for MeshOptions {
+    fn default() -> Self {
+        Self {
+            cell_size: 10.0,
+            wall_thickness: 2.0,
+            wall_height: 8.0,
+            floor_thickness: 2.0,
+        }
+    }
+}
+
+pub struct Lattice {
+    pub rows: usize,
+    pub columns: usize,
+    // sizes of each lattice column along x and each lattice row along y
+    pub widths: Vec<f32>,
+    pub depths: Vec<f32>,
+    pub solid: Vec<bool>,
+}
+
+impl Lattice {
+    pub fn is_solid(&self, row: usize, column: usize) -> bool {
+        self.solid[(row * self.columns) + column]
+    }
+
+    pub fn from_grid(grid: &dyn Grid, kind: GridKind, options: &MeshOptions) -> Self {
+        let dims = grid.dims();
+        match kind {
+            GridKind::Block => Self {
+                rows: dims.rows,
+                columns: dims.columns,
+                widths: vec![options.cell_size; dims.columns],
+                depths: vec![options.cell_size; dims.rows],
+                solid: grid
+                    .cells()
+                    .iter()
+                    .map(|cell| *cell == CellKind::Wall)
+                    .collect(),
+            },
+            GridKind::Wall => {
+                let (rows, columns) = ((2 * dims.rows) + 1, (2 * dims.columns) + 1);
+                let size = |line: usize| {
+                    if line % 2 == 0 {
+                        options.wall_thickness
+                    } else {
+                        options.cell_size
+                    }
+                };
+
+                let mut solid = Vec::with_capacity(rows * columns);
+                for row in 0..rows {
+                    for column in 0..columns {
+                        let (r, c) = (row / 2, column / 2);
+                        solid.push(match (row % 2, column % 2) {
+                            (0, 0) => true,
+                            (1, 1) => grid.get_cell(r, c) == CellKind::Wall,
+                            // a wall between the cells left and right of it
+                            (1, 0) => {
+                                c == 0 || c == dims.columns || !grid.is_passable((r, c - 1), (r, c))
+                            }
+                            // a wall between the cells below and above it
+                            _ => r == 0 || r == dims.rows || !grid.is_passable((r - 1, c), (r, c)),
+                        });
+                    }
+                }
+
+                Self {
+                    rows,
+                    columns,
+                    widths: (0..columns).map(size).collect(),
+                    depths: (0..rows).map(size).collect(),
+                    solid,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Mesh {
+    pub vertices: Vec<[f32; 3]>,
+    // counter clockwise seen from outside
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl Mesh {
+    pub fn from_grid(grid: &dyn Grid, kind: GridKind, options: &MeshOptions) -> Self {
+        extrude(&Lattice::from_grid(grid, kind, options), options)
+    }
+
+    pub fn normal_of(&self, triangle: [u32; 3]) -> [f32; 3] {
+        let [a, b, c] = triangle;
+        normal(
+            self.vertices[a as usize],
+            self.vertices[b as usize],
+            self.vertices[c as usize],
+        )
+    }
+
+    pub fn write_obj<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "# maze-rs")?;
+        for [x, y, z] in &self.vertices {
+            writeln!(writer, "v {} {} {}", x, y, z)?;
+        }
+        for [a, b, c] in &self.triangles {
+            writeln!(writer, "f {} {} {}", a + 1, b + 1, c + 1)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_stl<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut header = [0u8; 80];
+        header[..7].copy_from_slice(b"maze-rs");
+        writer.write_all(&header)?;
+        writer.write_all(&(self.triangles.len() as u32).to_le_bytes())?;
+
+        for triangle in &self.triangles {
+            let mut record = Vec::with_capacity(50);
+            let normal = self.normal_of(*triangle);
+            let corners = triangle.iter().map(|index| self.vertices[*index as usize]);
+            for point in std::iter::once(normal).chain(corners) {
+                for value in &point {
+                    record.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            record.extend_from_slice(&[0, 0]);
+            writer.write_all(&record)?;
+        }
+
+        Ok(())
+    }
+}
+
+// z levels of the solid, walls are split at the floor top so neighbouring faces always meet
+// on whole edges
+const BOTTOM: usize = 0;
+const FLOOR: usize = 1;
+const TOP: usize = 2;
+
+pub fn extrude(lattice: &Lattice, options: &MeshOptions) -> Mesh {
+    let mut builder = Builder {
+        lattice,
+        xs: prefix_sums(&lattice.widths),
+        ys: prefix_sums(&lattice.depths),
+        zs: [-options.floor_thickness, 0.0, options.wall_height],
+        lookup: HashMap::new(),
+        mesh: Mesh::default(),
+    };
+
+    for row in 0..lattice.rows {
+        for column in 0..lattice.columns {
+            builder.add_block(row, column);
+        }
+    }
+
+    builder.mesh
+}
+
+struct Builder<'a> {
+    lattice: &'a Lattice,
+    xs: Vec<f32>,
+    ys: Vec<f32>,
+    zs: [f32; 3],
+    // (line y, line x, level, owner tag) -> vertex
+    lookup: HashMap<(usize, usize, usize, usize), u32>,
+    mesh: Mesh,
+}
+
+impl<'a> Builder<'a> {
+    fn solid_at(&self, row: isize, column: isize) -> bool {
+        row >= 0
+            && column >= 0
+            && (row as usize) < self.lattice.rows
+            && (column as usize) < self.lattice.columns
+            && self.lattice.is_solid(row as usize, column as usize)
+    }
+
+    // two solid blocks touching only at this corner would share the vertical edge with four
+    // faces, so on top each one gets its own copy of the corner
+    fn vertex(&mut self, line: (usize, usize), level: usize, owner: (usize, usize)) -> u32 {
+        let (y, x) = (line.0 as isize, line.1 as isize);
+        let quadrants = [
+            self.solid_at(y - 1, x - 1),
+            self.solid_at(y - 1, x),
+            self.solid_at(y, x),
+            self.solid_at(y, x - 1),
+        ];
+        let diagonal = quadrants[0] == quadrants[2]
+            && quadrants[1] == quadrants[3]
+            && quadrants[0] != quadrants[1];
+        let tag = if level == TOP && diagonal {
+            1 + ((owner.0 == line.0) as usize * 2) + ((owner.1 == line.1) as usize)
+        } else {
+            0
+        };
+
+        let key = (line.0, line.1, level, tag);
+        if let Some(index) = self.lookup.get(&key) {
+            return *index;
+        }
+        let index = self.mesh.vertices.len() as u32;
+        self.mesh
+            .vertices
+            .push([self.xs[line.1], self.ys[line.0], self.zs[level]]);
+        self.lookup.insert(key, index);
+
+        index
+    }
+
+    fn quad(&mut self, corners: [u32; 4], outward: [f32; 3]) {
+        let [a, b, c, d] = corners;
+        let mut triangles = [[a, b, c], [a, c, d]];
+        let facing = self.mesh.normal_of(triangles[0]);
+        if dot(facing, outward) < 0.0 {
+            triangles = [[a, c, b], [a, d, c]];
+        }
+        self.mesh.triangles.extend_from_slice(&triangles);
+    }
+
+    // vertical face along a lattice line from one corner to the other, between two levels
+    fn side(
+        &mut self,
+        ends: [(usize, usize); 2],
+        levels: [usize; 2],
+        owner: (usize, usize),
+        outward: [f32; 3],
+    ) {
+        let corners = [
+            self.vertex(ends[0], levels[0], owner),
+            self.vertex(ends[1], levels[0], owner),
+            self.vertex(ends[1], levels[1], owner),
+            self.vertex(ends[0], levels[1], owner),
+        ];
+        self.quad(corners, outward);
+    }
+
+    fn add_block(&mut self, row: usize, column: usize) {
+        let block = (row, column);
+        let solid = self.lattice.is_solid(row, column);
+        let height = if solid { TOP } else { FLOOR };
+        let (sw, se, ne, nw) = (
+            (row, column),
+            (row, column + 1),
+            (row + 1, column + 1),
+            (row + 1, column),
+        );
+
+        let top = [
+            self.vertex(sw, height, block),
+            self.vertex(se, height, block),
+            self.vertex(ne, height, block),
+            self.vertex(nw, height, block),
+        ];
+        self.quad(top, [0.0, 0.0, 1.0]);
+        let bottom = [
+            self.vertex(sw, BOTTOM, block),
+            self.vertex(se, BOTTOM, block),
+            self.vertex(ne, BOTTOM, block),
+            self.vertex(nw, BOTTOM, block),
+        ];
+        self.quad(bottom, [0.0, 0.0, -1.0]);
+
+        // outer faces, split at the floor top
+        let border = |builder: &mut Self, ends: [(usize, usize); 2], outward: [f32; 3]| {
+            builder.side(ends, [BOTTOM, FLOOR], block, outward);
+            if solid {
+                builder.side(ends, [FLOOR, TOP], block, outward);
+            }
+        };
+        if column == 0 {
+            border(self, [sw, nw], [-1.0, 0.0, 0.0]);
+        }
+        if column + 1 == self.lattice.columns {
+            border(self, [se, ne], [1.0, 0.0, 0.0]);
+        }
+        if row == 0 {
+            border(self, [sw, se], [0.0, -1.0, 0.0]);
+        }
+        if row + 1 == self.lattice.rows {
+            border(self, [nw, ne], [0.0, 1.0, 0.0]);
+        }
+
+        // inner faces where a wall meets the floor, owned by the wall
+        if column + 1 < self.lattice.columns {
+            let east = (row, column + 1);
+            match (solid, self.lattice.is_solid(east.0, east.1)) {
+                (true, false) => self.side([se, ne], [FLOOR, TOP], block, [1.0, 0.0, 0.0]),
+                (false, true) => self.side([se, ne], [FLOOR, TOP], east, [-1.0, 0.0, 0.0]),
+                _ => {}
+            }
+        }
+        if row + 1 < self.lattice.rows {
+            let north = (row + 1, column);
+            match (solid, self.lattice.is_solid(north.0, north.1)) {
+                (true, false) => self.side([nw, ne], [FLOOR, TOP], block, [0.0, 1.0, 0.0]),
+                (false, true) => self.side([nw, ne], [FLOOR, TOP], north, [0.0, -1.0, 0.0]),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn prefix_sums(sizes: &[f32]) -> Vec<f32> {
+    let mut sums = vec![0.0];
+    for size in sizes {
+        sums.push(sums.last().unwrap() + size);
+    }
+
+    sums
+}
+
+fn normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> [f32; 3] {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let n = [
+        (u[1] * v[2]) - (u[2] * v[1]),
+        (u[2] * v[0]) - (u[0] * v[2]),
+        (u[0] * v[1]) - (u[1] * v[0]),
+    ];
+    let length = dot(n, n).sqrt();
+    if length == 0.0 {
+        n
+    } else {
+        [n[0] / length, n[1] / length, n[2] / length]
+    }
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (a[0] * b[0]) + (a[1] * b[1]) + (a[2] * b[2])
+}
+
+#[cfg(test)]
+mod test_mesh {
+    use super::*;
+    use crate::generators::{new_generator, GeneratorKind};
+    use crate::grids::block_grid::BlockGrid;
+
+    // closed and consistently wound: every edge is walked once in each direction
+    fn assert_manifold(mesh: &Mesh) {
+        let mut edges: HashMap<(u32, u32), usize> = HashMap::new();
+        for [a, b, c] in &mesh.triangles {
+            for edge in &[(*a, *b), (*b, *c), (*c, *a)] {
+                *edges.entry(*edge).or_insert(0) += 1;
+            }
+        }
+        for ((from, to), count) in &edges {
+            assert_eq!(*count, 1, "edge {} -> {} walked {} times", from, to, count);
+            assert_eq!(edges.get(&(*to, *from)), Some(&1));
+        }
+    }
+
+    #[test]
+    fn generated_wall_maze_is_manifold() {
+        let mut generator = new_generator(GeneratorKind::AldousBroder, 5, 6, GridKind::Wall, 9);
+        let mesh = Mesh::from_grid(
+            generator.generate_maze().unwrap(),
+            GridKind::Wall,
+            &MeshOptions::default(),
+        );
+        assert_manifold(&mesh);
+
+        let mut stl = Vec::new();
+        mesh.write_stl(&mut stl).unwrap();
+        assert_eq!(stl.len(), 84 + (50 * mesh.triangles.len()));
+    }
+
+    #[test]
+    fn diagonal_blocks_stay_manifold() {
+        let mut grid = BlockGrid::with_dims(2, 2);
+        grid.set_cell(0, 0, CellKind::Wall);
+        grid.set_cell(1, 1, CellKind::Wall);
+
+        assert_manifold(&Mesh::from_grid(
+            &grid,
+            GridKind::Block,
+            &MeshOptions::default(),
+        ));
+    }
+}
@@ -0,0 +1,36 @@
+pub mod animation;
+pub mod binary;
+pub mod dot;
+pub mod json;
+pub mod mesh;
+pub mod pdf;
+pub mod raster;
+pub mod segments;
+pub mod svg;
+pub mod text;
+
+use crate::grids::MAX_DIMENSION;
+use std::io;
+
+// formats report malformed input as InvalidData
+pub(crate) fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+// cell count of a maze read from a file, whose dimensions can't be trusted to be sane
+pub(crate) fn checked_cells(rows: usize, columns: usize) -> io::Result<usize> {
+    if rows == 0 || columns == 0 {
+        return Err(invalid_data("maze has no cells"));
+    }
+    if rows > MAX_DIMENSION || columns > MAX_DIMENSION {
+        return Err(invalid_data(format!(
+            "{}x{} is larger than {}x{}",
+            rows, columns, MAX_DIMENSION, MAX_DIMENSION
+        )));
+    }
+    rows.checked_mul(columns)
+        .ok_or_else(|| invalid_data("maze is too large"))
+}
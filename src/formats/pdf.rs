@@ -0,0 +1,397 @@
+// printable puzzle books. the pdf is written by hand, plain uncompressed content streams and
+// the built in helvetica font are all a page of mazes needs
+use crate::analysis::distances_from;
+use crate::error::MazeResult;
+use crate::formats::segments::{center_segments, merged_rects, wall_segments};
+use crate::generators::{generate_grid, GeneratorKind};
+use crate::grids::{CellKind, Grid, GridKind};
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+// us letter in points
+pub const PAGE_WIDTH: f32 = 612.0;
+pub const PAGE_HEIGHT: f32 = 792.0;
+const PAGE_MARGIN: f32 = 48.0;
+const TITLE_SIZE: f32 = 20.0;
+const LABEL_SIZE: f32 = 11.0;
+
+// one content stream per page
+#[derive(Debug, Default, Clone)]
+pub struct Page {
+    content: String,
+}
+
+impl Page {
+    pub fn text(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        let escaped = text
+            .replace('\\', "\\\\")
+            .replace('(', "\\(")
+            .replace(')', "\\)");
+        let _ = writeln!(
+            self.content,
+            "0 0 0 rg BT /F1 {} Tf {} {} Td ({}) Tj ET",
+            size, x, y, escaped
+        );
+    }
+
+    pub fn line(&mut self, from: [f32; 2], to: [f32; 2], width: f32, color: [f32; 4]) {
+        let _ = writeln!(
+            self.content,
+            "{} {} {} RG {} w 2 J {} {} m {} {} l S",
+            color[0], color[1], color[2], width, from[0], from[1], to[0], to[1]
+        );
+    }
+
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: [f32; 4]) {
+        let _ = writeln!(
+            self.content,
+            "{} {} {} rg {} {} {} {} re f",
+            color[0], color[1], color[2], x, y, width, height
+        );
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PdfDocument {
+    pub pages: Vec<Page>,
+}
+
+impl PdfDocument {
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        // objects 1 and 2 are the catalog and page tree, 3 the font, then a page and its
+        // content stream for every page
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                (0..self.pages.len())
+                    .map(|page| format!("{} 0 R", 4 + (page * 2)))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                self.pages.len()
+            ),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        ];
+        for (page, content) in self.pages.iter().enumerate() {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                5 + (page * 2)
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                content.content.len(),
+                content.content
+            ));
+        }
+
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            write!(pdf, "{} 0 obj\n{}\nendobj\n", index + 1, object)?;
+        }
+
+        let xref = pdf.len();
+        write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1)?;
+        for offset in offsets {
+            write!(pdf, "{:010} 00000 n \n", offset)?;
+        }
+        write!(
+            pdf,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )?;
+
+        writer.write_all(&pdf)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    // by how much of the maze the solution winds through
+    pub fn from_solution(solution_length: usize, open_cells: usize) -> Self {
+        let share = solution_length as f32 / open_cells.max(1) as f32;
+        if share < 0.15 {
+            Difficulty::Easy
+        } else if share < 0.25 {
+            Difficulty::Medium
+        } else if share < 0.4 {
+            Difficulty::Hard
+        } else {
+            Difficulty::Expert
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Expert => "Expert",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BookOptions {
+    pub title: String,
+    pub count: usize,
+    // 1, 2, 4 and 6 fill a page evenly
+    pub per_page: usize,
+    // block grids get bumped to odd sizes so the corners are maze cells
+    pub rows: usize,
+    pub columns: usize,
+    pub grid_kind: GridKind,
+    pub generator_kind: GeneratorKind,
+    // maze i is generated from seed + i
+    pub seed: u64,
+}
+
+impl Default for BookOptions {
+    fn default() -> Self {
+        Self {
+            title: "Mazes".to_string(),
+            count: 12,
+            per_page: 4,
+            rows: 20,
+            columns: 20,
+            grid_kind: GridKind::Wall,
+            generator_kind: GeneratorKind::AldousBroder,
+            seed: 0,
+        }
+    }
+}
+
+pub struct Puzzle {
+    pub seed: u64,
+    pub grid: Box<dyn Grid>,
+    // every cell from start to goal, both ends included
+    pub solution: Vec<(usize, usize)>,
+    pub difficulty: Difficulty,
+}
+
+// generates a maze with the start in the lower left corner and the goal in the upper right
+pub fn make_puzzle(options: &BookOptions, seed: u64) -> MazeResult<Puzzle> {
+    let (rows, columns) = match options.grid_kind {
+        GridKind::Block => (options.rows | 1, options.columns | 1),
+        GridKind::Wall => (options.rows, options.columns),
+    };
+    let mut grid = generate_grid(
+        options.generator_kind,
+        rows,
+        columns,
+        options.grid_kind,
+        seed,
+    )?;
+
+    let (start, goal) = match options.grid_kind {
+        GridKind::Block => ((1, 1), (rows - 2, columns - 2)),
+        GridKind::Wall => ((0, 0), (rows - 1, columns - 1)),
+    };
+    grid.set_start(Some(start));
+    grid.set_goal(Some(goal));
+
+    let solution = distances_from(grid.as_ref(), start)
+        .path_to(goal.0, goal.1)
+        .unwrap_or_default();
+    let open_cells = grid
+        .cells()
+        .iter()
+        .filter(|cell| **cell != CellKind::Wall)
+        .count();
+    let difficulty = Difficulty::from_solution(solution.len().saturating_sub(1), open_cells);
+
+    Ok(Puzzle {
+        seed,
+        grid,
+        solution,
+        difficulty,
+    })
+}
+
+// every puzzle first, then an answer key redrawing each one with its solution
+pub fn puzzle_book(options: &BookOptions) -> MazeResult<PdfDocument> {
+    let mut puzzles = (0..options.count as u64)
+        .map(|index| make_puzzle(options, options.seed + index))
+        .collect::<MazeResult<Vec<Puzzle>>>()?;
+
+    let mut document = PdfDocument::default();
+    lay_out(&mut document, options, &options.title, &puzzles, false);
+
+    for puzzle in &mut puzzles {
+        for (row, column) in &puzzle.solution {
+            if puzzle.grid.get_cell(*row, *column) == CellKind::Empty {
+                puzzle.grid.set_cell(*row, *column, CellKind::Path);
+            }
+        }
+    }
+    lay_out(&mut document, options, "Answers", &puzzles, true);
+
+    Ok(document)
+}
+
+fn lay_out(
+    document: &mut PdfDocument,
+    options: &BookOptions,
+    heading: &str,
+    puzzles: &[Puzzle],
+    show_solution: bool,
+) {
+    let per_page = options.per_page.max(1);
+    let slots_across = if per_page == 1 { 1 } else { 2 };
+    let slots_down = (per_page + slots_across - 1) / slots_across;
+
+    let top = PAGE_HEIGHT - PAGE_MARGIN - TITLE_SIZE - 12.0;
+    let slot_width = (PAGE_WIDTH - (2.0 * PAGE_MARGIN)) / slots_across as f32;
+    let slot_height = (top - PAGE_MARGIN) / slots_down as f32;
+
+    for (page_index, page_puzzles) in puzzles.chunks(per_page).enumerate() {
+        let mut page = Page::default();
+        let title = if page_index == 0 {
+            heading.to_string()
+        } else {
+            format!("{} (continued)", heading)
+        };
+        page.text(
+            PAGE_MARGIN,
+            PAGE_HEIGHT - PAGE_MARGIN - TITLE_SIZE,
+            TITLE_SIZE,
+            &title,
+        );
+
+        for (slot, puzzle) in page_puzzles.iter().enumerate() {
+            let number = (page_index * per_page) + slot + 1;
+            let x = PAGE_MARGIN + ((slot % slots_across) as f32 * slot_width);
+            let y = top - ((slot / slots_across) as f32 * slot_height);
+
+            let label = format!(
+                "#{}  {}  (seed {})",
+                number,
+                puzzle.difficulty.label(),
+                puzzle.seed
+            );
+            page.text(x + 6.0, y - LABEL_SIZE - 4.0, LABEL_SIZE, &label);
+
+            let inset = 6.0;
+            draw_maze(
+                &mut page,
+                puzzle.grid.as_ref(),
+                options.grid_kind,
+                [x + inset, y - slot_height + inset],
+                [
+                    slot_width - (2.0 * inset),
+                    slot_height - LABEL_SIZE - 12.0 - inset,
+                ],
+                show_solution,
+            );
+        }
+
+        document.pages.push(page);
+    }
+}
+
+// fits the maze inside the box keeping cells square, origin is the lower left corner
+pub fn draw_maze(
+    page: &mut Page,
+    grid: &dyn Grid,
+    kind: GridKind,
+    origin: [f32; 2],
+    size: [f32; 2],
+    show_solution: bool,
+) {
+    let dims = grid.dims();
+    let cell = (size[0] / dims.columns as f32).min(size[1] / dims.rows as f32);
+    let left = origin[0] + ((size[0] - (cell * dims.columns as f32)) / 2.0);
+    let bottom = origin[1] + ((size[1] - (cell * dims.rows as f32)) / 2.0);
+    let point = |[x, y]: [f32; 2]| [left + (x * cell), bottom + (y * cell)];
+
+    for end in &[CellKind::Start, CellKind::Goal] {
+        for rect in merged_rects(grid, |kind| kind == *end) {
+            let [x, y] = point([rect.column as f32, rect.row as f32]);
+            page.fill_rect(
+                x,
+                y,
+                rect.columns as f32 * cell,
+                rect.rows as f32 * cell,
+                (*end).into(),
+            );
+        }
+    }
+
+    if show_solution {
+        let on_route =
+            |kind| kind == CellKind::Path || kind == CellKind::Start || kind == CellKind::Goal;
+        for segment in center_segments(grid, on_route) {
+            page.line(
+                point(segment.from),
+                point(segment.to),
+                cell / 3.0,
+                CellKind::Path.into(),
+            );
+        }
+    }
+
+    match kind {
+        GridKind::Block => {
+            for rect in merged_rects(grid, |kind| kind == CellKind::Wall) {
+                let [x, y] = point([rect.column as f32, rect.row as f32]);
+                page.fill_rect(
+                    x,
+                    y,
+                    rect.columns as f32 * cell,
+                    rect.rows as f32 * cell,
+                    CellKind::Wall.into(),
+                );
+            }
+        }
+        GridKind::Wall => {
+            let thickness = (cell / 8.0).max(0.5);
+            for segment in wall_segments(grid) {
+                page.line(
+                    point(segment.from),
+                    point(segment.to),
+                    thickness,
+                    CellKind::Wall.into(),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_pdf {
+    use super::*;
+
+    #[test]
+    fn book_has_puzzle_and_answer_pages() {
+        let options = BookOptions {
+            count: 5,
+            per_page: 4,
+            rows: 6,
+            columns: 6,
+            ..BookOptions::default()
+        };
+        let mut bytes = Vec::new();
+        puzzle_book(&options).unwrap().write(&mut bytes).unwrap();
+        let pdf = String::from_utf8(bytes).unwrap();
+
+        assert!(pdf.starts_with("%PDF-1.4"));
+        assert!(pdf.contains("/Count 4"));
+        assert!(pdf.contains("(Answers) Tj"));
+
+        // the xref offset has to point at the xref table
+        let startxref = pdf.rfind("startxref\n").unwrap() + "startxref\n".len();
+        let offset: usize = pdf[startxref..].lines().next().unwrap().parse().unwrap();
+        assert!(pdf[offset..].starts_with("xref"));
+    }
+}
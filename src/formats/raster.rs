@@ -0,0 +1,288 @@
+// cpu only drawing of a grid into an rgba image, no window or gpu needed
+use crate::grids::{CellKind, Grid, GridKind};
+use std::io::{self, Write};
+
+#[derive(Debug, Clone)]
+pub struct RasterOptions {
+    // pixels across one cell, block grids draw every block at this size
+    pub cell_size: u32,
+    // pixels across a wall between two wall grid cells
+    pub wall_thickness: u32,
+    pub margin: u32,
+    pub background: [f32; 4],
+    pub wall: [f32; 4],
+    pub empty: [f32; 4],
+    pub start: [f32; 4],
+    pub goal: [f32; 4],
+    pub path: [f32; 4],
+    pub explored: [f32; 4],
+    pub cursor: [f32; 4],
+    pub show_path: bool,
+    pub show_explored: bool,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self {
+            cell_size: 16,
+            wall_thickness: 2,
+            margin: 8,
+            background: CellKind::Empty.into(),
+            wall: CellKind::Wall.into(),
+            empty: CellKind::Empty.into(),
+            start: CellKind::Start.into(),
+            goal: CellKind::Goal.into(),
+            path: CellKind::Path.into(),
+            explored: CellKind::Explored.into(),
+            cursor: CellKind::Cursor.into(),
+            show_path: true,
+            show_explored: false,
+        }
+    }
+}
+
+impl RasterOptions {
+    pub fn color_of(&self, kind: CellKind) -> [f32; 4] {
+        match kind {
+            CellKind::Empty => self.empty,
+            CellKind::Wall => self.wall,
+            CellKind::Start => self.start,
+            CellKind::Goal => self.goal,
+            CellKind::Path if self.show_path => self.path,
+            CellKind::Explored if self.show_explored => self.explored,
+            CellKind::Path | CellKind::Explored => self.empty,
+            CellKind::Cursor => self.cursor,
+        }
+    }
+}
+
+// largest image drawn, 1 GiB of rgba
+const MAX_PIXELS: usize = 1 << 28;
+
+fn too_large() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "image is too large, use a smaller cell size",
+    )
+}
+
+// rgba8 pixels, top row first
+#[derive(Debug, Clone, PartialEq)]
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, color: [f32; 4]) -> io::Result<Self> {
+        let pixels = (width as usize)
+            .checked_mul(height as usize)
+            .filter(|&pixels| pixels <= MAX_PIXELS)
+            .ok_or_else(too_large)?;
+        let mut canvas = Self {
+            width,
+            height,
+            pixels: vec![0; pixels * 4],
+        };
+        canvas.fill_rect(0, 0, width, height, color);
+
+        Ok(canvas)
+    }
+
+    pub fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: [f32; 4]) {
+        let rgba = to_rgba8(color);
+        for row in y..y.saturating_add(height).min(self.height) {
+            for column in x..x.saturating_add(width).min(self.width) {
+                let at = self.offset(column, row);
+                self.pixels[at..at + 4].copy_from_slice(&rgba);
+            }
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let at = self.offset(x, y);
+        [
+            self.pixels[at],
+            self.pixels[at + 1],
+            self.pixels[at + 2],
+            self.pixels[at + 3],
+        ]
+    }
+
+    fn offset(&self, x: u32, y: u32) -> usize {
+        ((y as usize * self.width as usize) + x as usize) * 4
+    }
+
+    pub fn write_png<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+
+        Ok(())
+    }
+}
+
+pub fn to_rgba8(color: [f32; 4]) -> [u8; 4] {
+    let channel = |value: f32| (value.max(0.0).min(1.0) * 255.0).round() as u8;
+    [
+        channel(color[0]),
+        channel(color[1]),
+        channel(color[2]),
+        channel(color[3]),
+    ]
+}
+
+// pixels across count cells of pitch each plus a fixed extra, refusing sizes past u32
+fn span(count: usize, pitch: u32, extra: u32) -> io::Result<u32> {
+    (count as u64)
+        .checked_mul(pitch as u64)
+        .and_then(|pixels| pixels.checked_add(extra as u64))
+        .filter(|&pixels| pixels <= u32::MAX as u64)
+        .map(|pixels| pixels as u32)
+        .ok_or_else(too_large)
+}
+
+pub fn rasterize(grid: &dyn Grid, kind: GridKind, options: &RasterOptions) -> io::Result<Canvas> {
+    match kind {
+        GridKind::Block => rasterize_blocks(grid, options),
+        GridKind::Wall => rasterize_walls(grid, options),
+    }
+}
+
+pub fn write_png<W: Write>(
+    grid: &dyn Grid,
+    kind: GridKind,
+    options: &RasterOptions,
+    writer: W,
+) -> io::Result<()> {
+    rasterize(grid, kind, options)?.write_png(writer)
+}
+
+fn rasterize_blocks(grid: &dyn Grid, options: &RasterOptions) -> io::Result<Canvas> {
+    let dims = grid.dims();
+    let size = options.cell_size;
+    let margins = options.margin.checked_mul(2).ok_or_else(too_large)?;
+    let mut canvas = Canvas::new(
+        span(dims.columns, size, margins)?,
+        span(dims.rows, size, margins)?,
+        options.background,
+    )?;
+
+    for row in 0..dims.rows {
+        // row 0 sits at the bottom like on screen
+        let y = options.margin + ((dims.rows - 1 - row) as u32 * size);
+        for column in 0..dims.columns {
+            let x = options.margin + (column as u32 * size);
+            let color = options.color_of(grid.get_cell(row, column));
+            canvas.fill_rect(x, y, size, size, color);
+        }
+    }
+
+    Ok(canvas)
+}
+
+fn rasterize_walls(grid: &dyn Grid, options: &RasterOptions) -> io::Result<Canvas> {
+    let dims = grid.dims();
+    let size = options.cell_size;
+    let thickness = options.wall_thickness;
+    let pitch = size.checked_add(thickness).ok_or_else(too_large)?;
+    let width = span(dims.columns, pitch, thickness)?;
+    let height = span(dims.rows, pitch, thickness)?;
+    let margins = options.margin.checked_mul(2).ok_or_else(too_large)?;
+
+    let mut canvas = Canvas::new(
+        width.checked_add(margins).ok_or_else(too_large)?,
+        height.checked_add(margins).ok_or_else(too_large)?,
+        options.background,
+    )?;
+    canvas.fill_rect(options.margin, options.margin, width, height, options.wall);
+
+    let origin = |row: usize, column: usize| {
+        (
+            options.margin + thickness + (column as u32 * pitch),
+            options.margin + thickness + ((dims.rows - 1 - row) as u32 * pitch),
+        )
+    };
+    // a passage takes the color of its cells when they agree, so solutions draw unbroken
+    let passage_color = |one: CellKind, two: CellKind| {
+        let (one, two) = (options.color_of(one), options.color_of(two));
+        if one == two {
+            one
+        } else {
+            options.empty
+        }
+    };
+
+    for row in 0..dims.rows {
+        for column in 0..dims.columns {
+            let (x, y) = origin(row, column);
+            let cell = grid.get_cell(row, column);
+            canvas.fill_rect(x, y, size, size, options.color_of(cell));
+
+            if column + 1 < dims.columns && grid.is_passable((row, column), (row, column + 1)) {
+                let color = passage_color(cell, grid.get_cell(row, column + 1));
+                canvas.fill_rect(x + size, y, thickness, size, color);
+            }
+            if row + 1 < dims.rows && grid.is_passable((row, column), (row + 1, column)) {
+                let color = passage_color(cell, grid.get_cell(row + 1, column));
+                canvas.fill_rect(x, y - thickness, size, thickness, color);
+            }
+        }
+    }
+
+    Ok(canvas)
+}
+
+#[cfg(test)]
+mod test_raster {
+    use super::*;
+    use crate::grids::wall_grid::WallGrid;
+
+    #[test]
+    fn wall_grid_layout() {
+        let mut grid = WallGrid::with_dims(2, 2);
+        grid.clear_wall_between((0, 0), (0, 1)).unwrap();
+        grid.clear_wall_between((0, 1), (1, 1)).unwrap();
+        grid.toggle_cell(0, 0, CellKind::Start);
+
+        let options = RasterOptions {
+            cell_size: 4,
+            wall_thickness: 1,
+            margin: 2,
+            ..RasterOptions::default()
+        };
+        let canvas = rasterize(&grid, GridKind::Wall, &options).unwrap();
+        // 2 cells of 4, 3 walls of 1, 2 margins of 2
+        assert_eq!((canvas.width, canvas.height), (15, 15));
+
+        let wall = to_rgba8(options.wall);
+        let empty = to_rgba8(options.empty);
+        // row 0 is the bottom row, start in the lower left
+        assert_eq!(canvas.pixel(3, 11), to_rgba8(options.start));
+        assert_eq!(canvas.pixel(8, 3), empty);
+        // open between the bottom cells, closed between the left cells
+        assert_eq!(canvas.pixel(7, 11), empty);
+        assert_eq!(canvas.pixel(3, 7), wall);
+        assert_eq!(canvas.pixel(8, 7), empty);
+        assert_eq!(canvas.pixel(0, 0), to_rgba8(options.background));
+
+        let mut bytes = Vec::new();
+        canvas.write_png(&mut bytes).unwrap();
+        assert_eq!(&bytes[1..4], b"PNG");
+    }
+
+    #[test]
+    fn oversized_images_are_refused() {
+        let grid = WallGrid::with_dims(64, 64);
+        let options = RasterOptions {
+            cell_size: 1 << 20,
+            ..RasterOptions::default()
+        };
+        let error = rasterize(&grid, GridKind::Wall, &options).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(Canvas::new(u32::MAX, u32::MAX, options.background).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+// vector shapes for exporters, neighbouring pieces merged so walls come out as long lines
+// instead of one piece per cell. everything is in cell units with row 0 at the bottom, so
+// y grows upward and each exporter flips it if its page does not
+use crate::grids::{CellKind, Grid};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    pub from: [f32; 2],
+    pub to: [f32; 2],
+}
+
+// a block of cells, lower left corner plus size
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub row: usize,
+    pub column: usize,
+    pub rows: usize,
+    pub columns: usize,
+}
+
+// wall grid walls on the cell lattice, the outer border included
+pub fn wall_segments(grid: &dyn Grid) -> Vec<Segment> {
+    let dims = grid.dims();
+    let mut segments = Vec::new();
+
+    // horizontal walls, y is the line under row y
+    for y in 0..=dims.rows {
+        let walled = |column: usize| {
+            y == 0 || y == dims.rows || !grid.is_passable((y - 1, column), (y, column))
+        };
+        for (start, end) in runs(dims.columns, walled) {
+            segments.push(Segment {
+                from: [start as f32, y as f32],
+                to: [end as f32, y as f32],
+            });
+        }
+    }
+
+    // vertical walls, x is the line left of column x
+    for x in 0..=dims.columns {
+        let walled =
+            |row: usize| x == 0 || x == dims.columns || !grid.is_passable((row, x - 1), (row, x));
+        for (start, end) in runs(dims.rows, walled) {
+            segments.push(Segment {
+                from: [x as f32, start as f32],
+                to: [x as f32, end as f32],
+            });
+        }
+    }
+
+    segments
+}
+
+// lines through the centers of neighbouring cells that are both on and open to each other
+pub fn center_segments(grid: &dyn Grid, on: impl Fn(CellKind) -> bool) -> Vec<Segment> {
+    let dims = grid.dims();
+    let is_on = |row: usize, column: usize| on(grid.get_cell(row, column));
+    let mut segments = Vec::new();
+
+    for row in 0..dims.rows {
+        let linked = |column: usize| {
+            is_on(row, column)
+                && is_on(row, column + 1)
+                && grid.is_passable((row, column), (row, column + 1))
+        };
+        for (start, end) in runs(dims.columns.saturating_sub(1), linked) {
+            segments.push(Segment {
+                from: [start as f32 + 0.5, row as f32 + 0.5],
+                to: [end as f32 + 0.5, row as f32 + 0.5],
+            });
+        }
+    }
+
+    for column in 0..dims.columns {
+        let linked = |row: usize| {
+            is_on(row, column)
+                && is_on(row + 1, column)
+                && grid.is_passable((row, column), (row + 1, column))
+        };
+        for (start, end) in runs(dims.rows.saturating_sub(1), linked) {
+            segments.push(Segment {
+                from: [column as f32 + 0.5, start as f32 + 0.5],
+                to: [column as f32 + 0.5, end as f32 + 0.5],
+            });
+        }
+    }
+
+    segments
+}
+
+// covers every cell that is on with as few rectangles as a greedy sweep finds, runs along each
+// row first and then grown upward while the rows above repeat the run exactly
+pub fn merged_rects(grid: &dyn Grid, on: impl Fn(CellKind) -> bool) -> Vec<Rect> {
+    let dims = grid.dims();
+    let mut covered = vec![false; dims.rows * dims.columns];
+    let is_free = |covered: &[bool], row: usize, column: usize| {
+        !covered[(row * dims.columns) + column] && on(grid.get_cell(row, column))
+    };
+
+    let mut rects = Vec::new();
+    for row in 0..dims.rows {
+        let mut column = 0;
+        while column < dims.columns {
+            if !is_free(&covered, row, column) {
+                column += 1;
+                continue;
+            }
+
+            let mut columns = 1;
+            while column + columns < dims.columns && is_free(&covered, row, column + columns) {
+                columns += 1;
+            }
+            let mut rows = 1;
+            while row + rows < dims.rows
+                && (column..column + columns).all(|c| is_free(&covered, row + rows, c))
+            {
+                rows += 1;
+            }
+
+            for r in row..row + rows {
+                for c in column..column + columns {
+                    covered[(r * dims.columns) + c] = true;
+                }
+            }
+            rects.push(Rect {
+                row,
+                column,
+                rows,
+                columns,
+            });
+            column += columns;
+        }
+    }
+
+    rects
+}
+
+// half open stretches of 0..len where set holds, as (start, end) lattice points
+fn runs(len: usize, set: impl Fn(usize) -> bool) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for index in 0..len {
+        match (set(index), start) {
+            (true, None) => start = Some(index),
+            (false, Some(from)) => {
+                runs.push((from, index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        runs.push((from, len));
+    }
+
+    runs
+}
+
+#[cfg(test)]
+mod test_segments {
+    use super::*;
+    use crate::grids::block_grid::BlockGrid;
+    use crate::grids::wall_grid::WallGrid;
+
+    #[test]
+    fn open_room_is_its_border() {
+        let mut grid = WallGrid::with_dims(2, 3);
+        for row in 0..2 {
+            for column in 0..3 {
+                if column + 1 < 3 {
+                    grid.clear_wall_between((row, column), (row, column + 1))
+                        .unwrap();
+                }
+                if row + 1 < 2 {
+                    grid.clear_wall_between((row, column), (row + 1, column))
+                        .unwrap();
+                }
+            }
+        }
+
+        let segments = wall_segments(&grid);
+        assert_eq!(segments.len(), 4);
+        assert!(segments.contains(&Segment {
+            from: [0.0, 2.0],
+            to: [3.0, 2.0]
+        }));
+    }
+
+    #[test]
+    fn filled_block_grid_is_one_rect() {
+        let mut grid = BlockGrid::with_dims(3, 4);
+        grid.fill();
+
+        let rects = merged_rects(&grid, |kind| kind == CellKind::Wall);
+        assert_eq!(
+            rects,
+            vec![Rect {
+                row: 0,
+                column: 0,
+                rows: 3,
+                columns: 4
+            }]
+        );
+    }
+}
@@ -0,0 +1,175 @@
+// vector export, one inkscape layer per kind of thing so it can be restyled after the fact.
+// sizes from RasterOptions are read as svg user units instead of pixels
+use crate::formats::raster::{to_rgba8, RasterOptions};
+use crate::formats::segments::{center_segments, merged_rects, wall_segments, Rect, Segment};
+use crate::grids::{CellKind, Grid, GridKind};
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+pub fn to_svg(grid: &dyn Grid, kind: GridKind, options: &RasterOptions) -> String {
+    let dims = grid.dims();
+    let size = options.cell_size as f32;
+    let margin = options.margin as f32;
+    let width = (dims.columns as f32 * size) + (2.0 * margin);
+    let height = (dims.rows as f32 * size) + (2.0 * margin);
+
+    // cell units with y up to page units with y down
+    let point = |[x, y]: [f32; 2]| (margin + (x * size), height - margin - (y * size));
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        r#"  <rect id="background" width="{}" height="{}" fill="{}"/>"#,
+        width,
+        height,
+        hex(options.background)
+    );
+
+    let rect = |svg: &mut String, rect: &Rect| {
+        let (x, y) = point([rect.column as f32, (rect.row + rect.rows) as f32]);
+        let _ = writeln!(
+            svg,
+            r#"    <rect x="{}" y="{}" width="{}" height="{}"/>"#,
+            x,
+            y,
+            rect.columns as f32 * size,
+            rect.rows as f32 * size
+        );
+    };
+    let line = |svg: &mut String, segment: &Segment| {
+        let (x1, y1) = point(segment.from);
+        let (x2, y2) = point(segment.to);
+        let _ = writeln!(
+            svg,
+            r#"    <line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+            x1, y1, x2, y2
+        );
+    };
+
+    if options.show_explored {
+        open_layer(
+            &mut svg,
+            "explored",
+            &format!(r#"fill="{}""#, hex(options.explored)),
+        );
+        for explored in merged_rects(grid, |kind| kind == CellKind::Explored) {
+            rect(&mut svg, &explored);
+        }
+        close_layer(&mut svg);
+    }
+
+    if options.show_path {
+        let style = format!(
+            r#"fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round""#,
+            hex(options.path),
+            size / 3.0
+        );
+        open_layer(&mut svg, "solution", &style);
+        let on_route =
+            |kind| kind == CellKind::Path || kind == CellKind::Start || kind == CellKind::Goal;
+        for segment in center_segments(grid, on_route) {
+            line(&mut svg, &segment);
+        }
+        close_layer(&mut svg);
+    }
+
+    for (name, cell_kind, color) in &[
+        ("start", CellKind::Start, options.start),
+        ("goal", CellKind::Goal, options.goal),
+    ] {
+        open_layer(&mut svg, name, &format!(r#"fill="{}""#, hex(*color)));
+        for cell in merged_rects(grid, |kind| kind == *cell_kind) {
+            rect(&mut svg, &cell);
+        }
+        close_layer(&mut svg);
+    }
+
+    match kind {
+        GridKind::Block => {
+            open_layer(
+                &mut svg,
+                "walls",
+                &format!(r#"fill="{}""#, hex(options.wall)),
+            );
+            for wall in merged_rects(grid, |kind| kind == CellKind::Wall) {
+                rect(&mut svg, &wall);
+            }
+        }
+        GridKind::Wall => {
+            let style = format!(
+                r#"fill="none" stroke="{}" stroke-width="{}" stroke-linecap="square""#,
+                hex(options.wall),
+                options.wall_thickness
+            );
+            open_layer(&mut svg, "walls", &style);
+            for wall in wall_segments(grid) {
+                line(&mut svg, &wall);
+            }
+        }
+    }
+    close_layer(&mut svg);
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn write_svg<W: Write>(
+    grid: &dyn Grid,
+    kind: GridKind,
+    options: &RasterOptions,
+    mut writer: W,
+) -> io::Result<()> {
+    writer.write_all(to_svg(grid, kind, options).as_bytes())
+}
+
+fn open_layer(svg: &mut String, name: &str, style: &str) {
+    let _ = writeln!(
+        svg,
+        r#"  <g id="{name}" inkscape:groupmode="layer" inkscape:label="{name}" {style}>"#,
+        name = name,
+        style = style
+    );
+}
+
+fn close_layer(svg: &mut String) {
+    svg.push_str("  </g>\n");
+}
+
+fn hex(color: [f32; 4]) -> String {
+    let [r, g, b, _] = to_rgba8(color);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+#[cfg(test)]
+mod test_svg {
+    use super::*;
+    use crate::grids::wall_grid::WallGrid;
+
+    #[test]
+    fn layers_and_merged_walls() {
+        // a straight corridor solved end to end
+        let mut grid = WallGrid::with_dims(1, 4);
+        for column in 0..3 {
+            grid.clear_wall_between((0, column), (0, column + 1))
+                .unwrap();
+        }
+        grid.toggle_cell(0, 0, CellKind::Start);
+        grid.toggle_cell(0, 3, CellKind::Goal);
+        grid.set_cell(0, 1, CellKind::Path);
+        grid.set_cell(0, 2, CellKind::Path);
+
+        let svg = to_svg(&grid, GridKind::Wall, &RasterOptions::default());
+        for layer in &["walls", "solution", "start", "goal"] {
+            assert!(svg.contains(&format!(r#"<g id="{}""#, layer)));
+        }
+        assert!(!svg.contains(r#"<g id="explored""#));
+        // top, bottom and both ends, plus one solution line
+        assert_eq!(svg.matches("<line").count(), 5);
+    }
+}
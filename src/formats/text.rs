@@ -0,0 +1,328 @@
+// plain text mazes, the top line of the text is the highest row so it reads the same way the
+// window draws it
+//
+// BlockGrid is one character per cell:
+//   #####
+//   #S  #
+//   ### #
+//   #G  #
+//   #####
+//
+// WallGrid draws posts, walls and 2 character wide cells:
+//   +--+--+
+//   |S    |
+//   +--+  +
+//   |G    |
+//   +--+--+
+use crate::formats::{checked_cells, invalid_data};
+use crate::grids::block_grid::BlockGrid;
+use crate::grids::wall_grid::WallGrid;
+use crate::grids::{CellKind, Grid, GridKind};
+use std::io;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WallStyle {
+    Ascii,
+    Unicode,
+}
+
+pub fn cell_char(kind: CellKind) -> char {
+    match kind {
+        CellKind::Empty => ' ',
+        CellKind::Wall => '#',
+        CellKind::Start => 'S',
+        CellKind::Goal => 'G',
+        CellKind::Path => '.',
+        CellKind::Explored => ',',
+        CellKind::Cursor => '@',
+    }
+}
+
+pub fn char_cell(code: char) -> Option<CellKind> {
+    Some(match code {
+        ' ' => CellKind::Empty,
+        '#' => CellKind::Wall,
+        'S' => CellKind::Start,
+        'G' => CellKind::Goal,
+        '.' => CellKind::Path,
+        ',' => CellKind::Explored,
+        '@' => CellKind::Cursor,
+        _ => return None,
+    })
+}
+
+pub fn to_text(grid: &dyn Grid, kind: GridKind, style: WallStyle) -> String {
+    match kind {
+        GridKind::Block => block_to_text(grid),
+        GridKind::Wall => wall_to_text(grid, style),
+    }
+}
+
+pub fn block_to_text(grid: &dyn Grid) -> String {
+    let dims = grid.dims();
+    let mut text = String::with_capacity((dims.columns + 1) * dims.rows);
+
+    for row in (0..dims.rows).rev() {
+        for column in 0..dims.columns {
+            text.push(cell_char(grid.get_cell(row, column)));
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+pub fn wall_to_text(grid: &dyn Grid, style: WallStyle) -> String {
+    let dims = grid.dims();
+    // wall along the bottom edge of (boundary, column), boundary 0 is the bottom of the maze
+    let horizontal = |boundary: usize, column: usize| {
+        boundary == 0
+            || boundary == dims.rows
+            || !grid.is_passable((boundary - 1, column), (boundary, column))
+    };
+    // wall along the left edge of (row, boundary), boundary 0 is the left of the maze
+    let vertical = |row: usize, boundary: usize| {
+        boundary == 0
+            || boundary == dims.columns
+            || !grid.is_passable((row, boundary - 1), (row, boundary))
+    };
+    let (h_wall, v_wall) = match style {
+        WallStyle::Ascii => ("--", '|'),
+        WallStyle::Unicode => ("──", '│'),
+    };
+
+    let mut text = String::new();
+    for boundary in (0..=dims.rows).rev() {
+        for post in 0..=dims.columns {
+            let up = boundary < dims.rows && vertical(boundary, post);
+            let down = boundary > 0 && vertical(boundary - 1, post);
+            let left = post > 0 && horizontal(boundary, post - 1);
+            let right = post < dims.columns && horizontal(boundary, post);
+            text.push(post_char(style, up, down, left, right));
+
+            if post < dims.columns {
+                text.push_str(if right { h_wall } else { "  " });
+            }
+        }
+        text.push('\n');
+
+        if boundary == 0 {
+            break;
+        }
+
+        let row = boundary - 1;
+        for column in 0..=dims.columns {
+            text.push(if vertical(row, column) { v_wall } else { ' ' });
+            if column < dims.columns {
+                text.push(cell_char(grid.get_cell(row, column)));
+                text.push(' ');
+            }
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+fn post_char(style: WallStyle, up: bool, down: bool, left: bool, right: bool) -> char {
+    if style == WallStyle::Ascii {
+        return '+';
+    }
+
+    match (up, down, left, right) {
+        (true, true, true, true) => '┼',
+        (true, true, true, false) => '┤',
+        (true, true, false, true) => '├',
+        (true, false, true, true) => '┴',
+        (false, true, true, true) => '┬',
+        (true, true, false, false) => '│',
+        (false, false, true, true) => '─',
+        (true, false, true, false) => '┘',
+        (true, false, false, true) => '└',
+        (false, true, true, false) => '┐',
+        (false, true, false, true) => '┌',
+        (true, false, false, false) => '╵',
+        (false, true, false, false) => '╷',
+        (false, false, true, false) => '╴',
+        (false, false, false, true) => '╶',
+        (false, false, false, false) => ' ',
+    }
+}
+
+// guesses the grid kind from the first character, wall grids always start on a post
+pub fn parse(text: &str) -> io::Result<(GridKind, Box<dyn Grid>)> {
+    match text.trim_start_matches('\n').chars().next() {
+        Some('+') | Some('┌') | Some('╶') | Some('╷') => {
+            Ok((GridKind::Wall, Box::new(parse_wall_grid(text)?)))
+        }
+        _ => Ok((GridKind::Block, Box::new(parse_block_grid(text)?))),
+    }
+}
+
+// trailing whitespace tends to get lost when pasting, so short lines are padded with spaces
+fn text_lines(text: &str) -> Vec<Vec<char>> {
+    let mut lines: Vec<Vec<char>> = text
+        .trim_start_matches('\n')
+        .lines()
+        .map(|line| line.trim_end_matches('\r').chars().collect())
+        .collect();
+    while lines
+        .last()
+        .map_or(false, |line| line.iter().all(|c| *c == ' '))
+    {
+        lines.pop();
+    }
+
+    lines
+}
+
+fn char_at(lines: &[Vec<char>], line: usize, position: usize) -> char {
+    *lines[line].get(position).unwrap_or(&' ')
+}
+
+fn parse_cell(code: char, line: usize, position: usize) -> io::Result<CellKind> {
+    char_cell(code).ok_or_else(|| {
+        invalid_data(format!(
+            "unknown cell {:?} at line {}, column {}",
+            code,
+            line + 1,
+            position + 1
+        ))
+    })
+}
+
+fn place_ends(
+    start: &mut Option<(usize, usize)>,
+    goals: &mut Vec<(usize, usize)>,
+    kind: CellKind,
+    coords: (usize, usize),
+) -> io::Result<()> {
+    match kind {
+        CellKind::Start if start.is_some() => Err(invalid_data("more than one start")),
+        CellKind::Start => {
+            *start = Some(coords);
+            Ok(())
+        }
+        CellKind::Goal => {
+            goals.push(coords);
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn parse_block_grid(text: &str) -> io::Result<BlockGrid> {
+    let lines = text_lines(text);
+    let rows = lines.len();
+    let columns = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+    if rows == 0 || columns == 0 {
+        return Err(invalid_data("empty maze"));
+    }
+    // one long line pads every other line out to it
+    checked_cells(rows, columns)?;
+
+    let mut grid = BlockGrid::with_dims(rows, columns);
+    for line in 0..rows {
+        let row = rows - 1 - line;
+        for column in 0..columns {
+            let kind = parse_cell(char_at(&lines, line, column), line, column)?;
+            place_ends(&mut grid.start, &mut grid.goals, kind, (row, column))?;
+            grid.set_cell(row, column, kind);
+        }
+    }
+    grid.goals.sort();
+    grid.goal = grid.goals.first().cloned();
+    grid.multi_goal = grid.goals.len() > 1;
+
+    Ok(grid)
+}
+
+pub fn parse_wall_grid(text: &str) -> io::Result<WallGrid> {
+    let lines = text_lines(text);
+    if lines.len() < 3 || lines.len() % 2 == 0 || lines[0].len() < 4 {
+        return Err(invalid_data(
+            "wall maze needs an odd number of lines, at least 3",
+        ));
+    }
+    let rows = (lines.len() - 1) / 2;
+    let columns = (lines[0].len() - 1) / 3;
+    checked_cells(rows, columns)?;
+
+    let is_wall = |code: char| !code.is_whitespace();
+    let mut grid = WallGrid::with_dims(rows, columns);
+    for row in 0..rows {
+        // cell line of this row and the boundary line below it
+        let line = (2 * (rows - 1 - row)) + 1;
+        let below = line + 1;
+
+        for column in 0..columns {
+            let position = (3 * column) + 1;
+            let kind = parse_cell(char_at(&lines, line, position), line, position)?;
+            place_ends(&mut grid.start, &mut grid.goals, kind, (row, column))?;
+            grid.set_cell(row, column, kind);
+
+            if column + 1 < columns && !is_wall(char_at(&lines, line, position + 2)) {
+                grid.clear_wall_between((row, column), (row, column + 1))?;
+            }
+            if row > 0 && !is_wall(char_at(&lines, below, position)) {
+                grid.clear_wall_between((row, column), (row - 1, column))?;
+            }
+        }
+    }
+    grid.goals.sort();
+    grid.goal = grid.goals.first().cloned();
+    grid.multi_goal = grid.goals.len() > 1;
+
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod test_text {
+    use super::*;
+    use crate::grids::MAX_DIMENSION;
+
+    const WALL_MAZE: &str = "\
++--+--+--+
+|G    |  |
++--+  +  +
+|S  .    |
++--+--+--+
+";
+
+    #[test]
+    fn wall_round_trip() {
+        let grid = parse_wall_grid(WALL_MAZE).unwrap();
+        assert_eq!(grid.start, Some((0, 0)));
+        assert_eq!(grid.goal, Some((1, 0)));
+        assert_eq!(grid.get_cell(0, 1), CellKind::Path);
+        assert!(grid.is_passable((0, 1), (1, 1)));
+        assert!(!grid.is_passable((1, 1), (1, 2)));
+
+        assert_eq!(wall_to_text(&grid, WallStyle::Ascii), WALL_MAZE);
+
+        let unicode = wall_to_text(&grid, WallStyle::Unicode);
+        assert!(unicode.starts_with("┌─────┬──┐"));
+        let reparsed = parse_wall_grid(&unicode).unwrap();
+        assert_eq!(reparsed.paths(), grid.paths());
+        assert_eq!(reparsed.cells, grid.cells);
+    }
+
+    #[test]
+    fn block_round_trip_with_stripped_whitespace() {
+        let text = "#####\n#G  #\n### #\n#S\n#####\n";
+        let grid = parse_block_grid(text).unwrap();
+        assert_eq!(grid.dims.columns, 5);
+        assert_eq!(grid.start, Some((1, 1)));
+        assert_eq!(grid.goal, Some((3, 1)));
+        assert_eq!(grid.get_cell(1, 4), CellKind::Empty);
+
+        assert_eq!(block_to_text(&grid), "#####\n#G  #\n### #\n#S   \n#####\n");
+    }
+
+    #[test]
+    fn one_long_line_is_not_padded_into_a_huge_maze() {
+        let text = format!("#\n#\n{}\n", "#".repeat(MAX_DIMENSION + 1));
+        let error = parse_block_grid(&text).err().map(|e| e.kind());
+        assert_eq!(error, Some(io::ErrorKind::InvalidData));
+    }
+}
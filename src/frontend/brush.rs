@@ -0,0 +1,232 @@
+// editor tools. a stroke runs from mouse press to release so the whole drag lands in the undo
+// history as one edit
+use crate::error::MazeResult;
+use crate::grids::{CellKind, Grid};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Tool {
+    // paints every cell dragged over
+    Brush,
+    Rectangle,
+    Line,
+    // walls between cells rather than the cells, wall grids only
+    Walls,
+}
+
+pub type Edge = ((usize, usize), (usize, usize));
+
+// what a stroke changed, each as it was beforehand. a cell painted twice shows up twice
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Changes {
+    pub cells: Vec<((usize, usize), CellKind)>,
+    // and whether the wall was open
+    pub walls: Vec<(Edge, bool)>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty() && self.walls.is_empty()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Stroke {
+    pub tool: Tool,
+    pub kind: CellKind,
+    pub erase: bool,
+    pub anchor: (usize, usize),
+    pub last: (usize, usize),
+    last_edge: Option<Edge>,
+    changes: Changes,
+}
+
+impl Stroke {
+    // brushes paint straight away, shapes wait for finish
+    pub fn begin(
+        grid: &mut dyn Grid,
+        tool: Tool,
+        kind: CellKind,
+        erase: bool,
+        cell: (usize, usize),
+    ) -> Self {
+        let mut changes = Changes::default();
+        if tool == Tool::Brush {
+            paint(grid, cell, kind, erase, &mut changes);
+        }
+        Self {
+            tool,
+            kind,
+            erase,
+            anchor: cell,
+            last: cell,
+            last_edge: None,
+            changes,
+        }
+    }
+
+    pub fn drag(&mut self, grid: &mut dyn Grid, cell: (usize, usize)) {
+        // fast drags skip cells between mouse events, so brush along the line between them
+        if self.tool == Tool::Brush && cell != self.last {
+            for cell in line_cells(self.last, cell).into_iter().skip(1) {
+                paint(grid, cell, self.kind, self.erase, &mut self.changes);
+            }
+        }
+        self.last = cell;
+    }
+
+    pub fn drag_edge(&mut self, grid: &mut dyn Grid, edge: Edge) -> MazeResult<()> {
+        if self.last_edge == Some(edge) {
+            return Ok(());
+        }
+        self.last_edge = Some(edge);
+        let open = grid.is_passable(edge.0, edge.1);
+        self.changes.walls.push((edge, open));
+        if self.erase {
+            grid.clear_wall_between(edge.0, edge.1)
+        } else {
+            grid.add_wall_between(edge.0, edge.1)
+        }
+    }
+
+    // everything changed since the last call
+    pub fn take_changes(&mut self) -> Changes {
+        std::mem::take(&mut self.changes)
+    }
+
+    // shapes are painted now, hands back whatever take_changes hasn't
+    pub fn finish(mut self, grid: &mut dyn Grid) -> Changes {
+        if self.tool == Tool::Rectangle || self.tool == Tool::Line {
+            for cell in self.preview() {
+                paint(grid, cell, self.kind, self.erase, &mut self.changes);
+            }
+        }
+        self.changes
+    }
+
+    // cells a rectangle or line would paint if released now
+    pub fn preview(&self) -> Vec<(usize, usize)> {
+        match self.tool {
+            Tool::Rectangle => rect_cells(self.anchor, self.last),
+            Tool::Line => line_cells(self.anchor, self.last),
+            Tool::Brush | Tool::Walls => Vec::new(),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match (self.tool, self.erase) {
+            (Tool::Walls, false) => "Walls".to_string(),
+            (Tool::Walls, true) => "Erase Walls".to_string(),
+            (tool, true) => format!("Erase {:?}", tool),
+            (tool, false) => format!("{:?} {:?}", self.kind, tool),
+        }
+    }
+}
+
+// sets the cell to kind, or empties it when erasing, without leaving a stale start or goal behind.
+// the cell and any start or goal it moves go into changes first
+pub fn paint(
+    grid: &mut dyn Grid,
+    cell: (usize, usize),
+    kind: CellKind,
+    erase: bool,
+    changes: &mut Changes,
+) {
+    let (row, column) = cell;
+    let current = grid.get_cell(row, column);
+    if !erase && current == kind {
+        return;
+    }
+
+    changes.cells.push((cell, current));
+    let moved = match kind {
+        CellKind::Start if !erase => grid.start().into_iter().collect(),
+        CellKind::Goal if !erase && !grid.multi_goal() => grid.goals(),
+        _ => Vec::new(),
+    };
+    for end in moved {
+        changes.cells.push((end, grid.get_cell(end.0, end.1)));
+    }
+
+    match current {
+        CellKind::Start => grid.set_start(None),
+        CellKind::Goal => {
+            grid.toggle_cell(row, column, CellKind::Goal);
+        }
+        _ => {
+            grid.set_cell(row, column, CellKind::Empty);
+        }
+    }
+    if !erase {
+        grid.toggle_cell(row, column, kind);
+    }
+}
+
+// every cell between the corners, both included
+pub fn rect_cells(one: (usize, usize), two: (usize, usize)) -> Vec<(usize, usize)> {
+    let rows = one.0.min(two.0)..=one.0.max(two.0);
+    rows.flat_map(|row| (one.1.min(two.1)..=one.1.max(two.1)).map(move |column| (row, column)))
+        .collect()
+}
+
+// bresenham, from one to two both included
+pub fn line_cells(one: (usize, usize), two: (usize, usize)) -> Vec<(usize, usize)> {
+    let (mut row, mut column) = (one.0 as isize, one.1 as isize);
+    let (end_row, end_column) = (two.0 as isize, two.1 as isize);
+    let d_row = (end_row - row).abs();
+    let d_column = -(end_column - column).abs();
+    let step_row = if row < end_row { 1 } else { -1 };
+    let step_column = if column < end_column { 1 } else { -1 };
+    let mut error = d_row + d_column;
+
+    let mut cells = Vec::new();
+    loop {
+        cells.push((row as usize, column as usize));
+        if row == end_row && column == end_column {
+            return cells;
+        }
+        let doubled = 2 * error;
+        if doubled >= d_column {
+            error += d_column;
+            row += step_row;
+        }
+        if doubled <= d_row {
+            error += d_row;
+            column += step_column;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_brush {
+    use super::*;
+    use crate::grids::wall_grid::WallGrid;
+
+    #[test]
+    fn drags_fill_the_cells_they_skip() {
+        let mut grid = WallGrid::with_dims(5, 5);
+        let mut stroke = Stroke::begin(&mut grid, Tool::Brush, CellKind::Wall, false, (0, 0));
+        stroke.drag(&mut grid, (4, 2));
+        assert_eq!(stroke.take_changes().cells.len(), 5);
+        let walls = grid.cells.iter().filter(|cell| **cell == CellKind::Wall);
+        assert_eq!(walls.count(), 5);
+        assert_eq!(line_cells((4, 2), (0, 0)).len(), 5);
+
+        // painting over the start moves nothing but the start itself
+        grid.toggle_cell(2, 2, CellKind::Start);
+        let mut stroke = Stroke::begin(&mut grid, Tool::Rectangle, CellKind::Goal, true, (1, 1));
+        stroke.drag(&mut grid, (3, 3));
+        assert_eq!(stroke.preview().len(), 9);
+        let changes = stroke.finish(&mut grid);
+        assert_eq!(changes.cells.len(), 9);
+        assert!(changes.cells.contains(&((2, 2), CellKind::Start)));
+        assert_eq!(grid.start, None);
+        assert_eq!(grid.get_cell(2, 1), CellKind::Empty);
+        assert_eq!(grid.get_cell(4, 2), CellKind::Wall);
+
+        let mut stroke = Stroke::begin(&mut grid, Tool::Walls, CellKind::Wall, true, (0, 0));
+        stroke.drag_edge(&mut grid, ((0, 0), (0, 1))).unwrap();
+        assert!(grid.is_passable((0, 0), (0, 1)));
+        assert_eq!(stroke.take_changes().walls, vec![(((0, 0), (0, 1)), false)]);
+        assert_eq!(stroke.label(), "Erase Walls");
+    }
+}
@@ -0,0 +1,189 @@
+// per cell data for the instanced cell pipeline, and the bookkeeping that keeps the gpu copy in
+// step by only handing over the cells that changed
+use super::view::Camera;
+use crate::grids::{CellKind, Dimensions, Grid, GridKind};
+use std::ops::Range;
+use winit::dpi::PhysicalSize;
+
+// what cell.frag draws in the gaps east and north of a cell
+pub const NO_GAP: u8 = 0;
+pub const OPEN: u8 = 1;
+pub const WALLED: u8 = 2;
+
+// changed cells closer together than this are uploaded as one write
+const MERGE_DISTANCE: usize = 64;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Instance {
+    pub color: [u8; 4],
+    // east and north gaps, then padding
+    pub walls: [u8; 4],
+}
+
+impl Instance {
+    pub fn of(grid: &dyn Grid, grid_kind: GridKind, index: usize, color: [f32; 4]) -> Self {
+        let dims = grid.dims();
+        let cell = (index / dims.columns, index % dims.columns);
+        // block grid walls are cells of their own, so only wall grids draw gaps
+        let gap = |neighbor: (usize, usize), exists: bool| {
+            if grid_kind != GridKind::Wall || !exists {
+                NO_GAP
+            } else if grid.is_passable(cell, neighbor) {
+                OPEN
+            } else {
+                WALLED
+            }
+        };
+        let channel = |value: f32| (value.max(0.0).min(1.0) * 255.0).round() as u8;
+
+        Self {
+            color: [
+                channel(color[0]),
+                channel(color[1]),
+                channel(color[2]),
+                channel(color[3]),
+            ],
+            walls: [
+                gap((cell.0, cell.1 + 1), cell.1 + 1 < dims.columns),
+                gap((cell.0 + 1, cell.1), cell.0 + 1 < dims.rows),
+                0,
+                0,
+            ],
+        }
+    }
+}
+
+// the camera as cell.vert sees it, laid out for a std140 uniform block
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CellUniforms {
+    pub center: [f32; 2],
+    // ndc per cell on each axis
+    pub scale: [f32; 2],
+    pub columns: u32,
+    pub gap: f32,
+    _padding: [f32; 2],
+}
+
+impl CellUniforms {
+    pub fn new(camera: &Camera, dims: Dimensions, size: PhysicalSize<u32>) -> Self {
+        Self {
+            center: [camera.center.0, camera.center.1],
+            scale: [
+                camera.zoom * 2.0 / size.width as f32,
+                camera.zoom * 2.0 / size.height as f32,
+            ],
+            columns: dims.columns as u32,
+            gap: camera.gap(),
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+// instances are cell indices, so the rows in the window are one range of them. the gpu clips
+// whatever sticks out sideways
+pub fn visible_instances(camera: &Camera, dims: Dimensions, size: PhysicalSize<u32>) -> Range<u32> {
+    let (rows, _) = camera.visible(dims, size);
+    let columns = dims.columns as u32;
+    (rows.start as u32 * columns)..(rows.end as u32 * columns)
+}
+
+#[derive(Default)]
+pub struct InstanceTracker {
+    instances: Vec<Instance>,
+    // walls may have moved anywhere, or the grid was replaced
+    stale: bool,
+    // cells whose kind, colour or walls changed
+    marked: Vec<usize>,
+}
+
+impl InstanceTracker {
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    pub fn mark(&mut self, index: usize) {
+        self.marked.push(index);
+    }
+
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    // brings the instances up to date with the grid, handing back the ranges that changed
+    pub fn update(
+        &mut self,
+        grid: &dyn Grid,
+        grid_kind: GridKind,
+        color: impl Fn(usize, CellKind) -> [f32; 4],
+    ) -> Vec<Range<usize>> {
+        let cells = grid.cells();
+        if self.stale || cells.len() != self.instances.len() {
+            self.instances = cells
+                .iter()
+                .enumerate()
+                .map(|(index, kind)| Instance::of(grid, grid_kind, index, color(index, *kind)))
+                .collect();
+            self.stale = false;
+            self.marked.clear();
+            return vec![0..cells.len()];
+        }
+
+        let mut changed = std::mem::take(&mut self.marked);
+        changed.sort_unstable();
+        changed.dedup();
+
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for index in changed.into_iter().filter(|index| *index < cells.len()) {
+            let kind = cells[index];
+            self.instances[index] = Instance::of(grid, grid_kind, index, color(index, kind));
+            match ranges.last_mut() {
+                Some(range) if index < range.end + MERGE_DISTANCE => range.end = index + 1,
+                _ => ranges.push(index..index + 1),
+            }
+        }
+        ranges
+    }
+}
+
+#[cfg(test)]
+mod test_instances {
+    use super::*;
+    use crate::grids::wall_grid::WallGrid;
+
+    #[test]
+    fn only_changed_cells_are_handed_over() {
+        let mut grid = WallGrid::with_dims(30, 30);
+        let mut tracker = InstanceTracker::default();
+        let color = |_, kind: CellKind| kind.into();
+
+        assert_eq!(tracker.update(&grid, GridKind::Wall, color), vec![0..900]);
+        assert_eq!(tracker.instances()[0].walls, [WALLED, WALLED, 0, 0]);
+        assert_eq!(tracker.instances()[899].walls, [NO_GAP, NO_GAP, 0, 0]);
+        assert!(tracker.update(&grid, GridKind::Wall, color).is_empty());
+
+        // nearby cells share a write, far apart ones get their own
+        grid.set_cell(0, 5, CellKind::Wall);
+        grid.set_cell(0, 7, CellKind::Path);
+        grid.set_cell(16, 20, CellKind::Explored);
+        for index in &[7, 5, 500, 7] {
+            tracker.mark(*index);
+        }
+        assert_eq!(
+            tracker.update(&grid, GridKind::Wall, color),
+            vec![5..8, 500..501]
+        );
+        assert_eq!(tracker.instances()[5].color, [0, 0, 0, 255]);
+
+        // whoever changes a cell or moves a wall says where
+        grid.clear_wall_between((0, 0), (0, 1)).unwrap();
+        tracker.mark(0);
+        assert_eq!(tracker.update(&grid, GridKind::Wall, color), vec![0..1]);
+        assert_eq!(tracker.instances()[0].walls, [OPEN, WALLED, 0, 0]);
+
+        tracker.invalidate();
+        assert_eq!(tracker.update(&grid, GridKind::Block, color), vec![0..900]);
+        assert_eq!(tracker.instances()[0].walls, [NO_GAP, NO_GAP, 0, 0]);
+    }
+}
@@ -0,0 +1,5 @@
+// everything that needs a window, only built with the gui feature
+pub mod brush;
+pub mod instances;
+pub mod renderer;
+pub mod view;
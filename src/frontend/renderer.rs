@@ -0,0 +1,285 @@
+use winit::window::Window;
+
+use wgpu::util::DeviceExt;
+
+use super::instances::{CellUniforms, Instance};
+use std::ops::Range;
+
+unsafe impl bytemuck::Pod for Instance {}
+unsafe impl bytemuck::Zeroable for Instance {}
+unsafe impl bytemuck::Pod for CellUniforms {}
+unsafe impl bytemuck::Zeroable for CellUniforms {}
+
+// the unit quad every cell instance is drawn from, in two triangles
+const CORNERS: &[[f32; 2]] = &[
+    [0.0, 0.0],
+    [1.0, 0.0],
+    [0.0, 1.0],
+    [0.0, 1.0],
+    [1.0, 0.0],
+    [1.0, 1.0],
+];
+
+fn corner_desc<'a>() -> wgpu::VertexBufferDescriptor<'a> {
+    wgpu::VertexBufferDescriptor {
+        stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+        step_mode: wgpu::InputStepMode::Vertex,
+        attributes: &[wgpu::VertexAttributeDescriptor {
+            offset: 0,
+            shader_location: 0,
+            format: wgpu::VertexFormat::Float2,
+        }],
+    }
+}
+
+fn instance_desc<'a>() -> wgpu::VertexBufferDescriptor<'a> {
+    wgpu::VertexBufferDescriptor {
+        stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+        step_mode: wgpu::InputStepMode::Instance,
+        attributes: &[
+            wgpu::VertexAttributeDescriptor {
+                offset: 0,
+                shader_location: 1,
+                format: wgpu::VertexFormat::Uchar4Norm,
+            },
+            wgpu::VertexAttributeDescriptor {
+                offset: std::mem::size_of::<[u8; 4]>() as wgpu::BufferAddress,
+                shader_location: 2,
+                format: wgpu::VertexFormat::Uchar4,
+            },
+        ],
+    }
+}
+
+fn create_pipeline(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::PipelineLayout,
+    shaders: (&wgpu::ShaderModule, &wgpu::ShaderModule),
+    format: wgpu::TextureFormat,
+    vertex_buffers: &[wgpu::VertexBufferDescriptor],
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(layout),
+        vertex_stage: wgpu::ProgrammableStageDescriptor {
+            module: shaders.0,
+            entry_point: "main",
+        },
+        fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+            // 2.
+            module: shaders.1,
+            entry_point: "main",
+        }),
+
+        rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: wgpu::CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        color_states: &[wgpu::ColorStateDescriptor {
+            format,
+            color_blend: wgpu::BlendDescriptor::REPLACE,
+            alpha_blend: wgpu::BlendDescriptor::REPLACE,
+            write_mask: wgpu::ColorWrite::ALL,
+        }],
+
+        primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+        depth_stencil_state: None,
+        vertex_state: wgpu::VertexStateDescriptor {
+            index_format: wgpu::IndexFormat::Uint16,
+            vertex_buffers,
+        },
+        sample_count: 1,
+        sample_mask: !0,
+        alpha_to_coverage_enabled: false,
+    })
+}
+
+//@TODO take queue/device and others off this and pass in when needed to allow imgui to use
+pub struct GraphicsContext {
+    pub size: winit::dpi::PhysicalSize<u32>,
+    pub clear_color: wgpu::Color,
+
+    pub(crate) command_encoder: Option<wgpu::CommandEncoder>,
+
+    // cells are instances of one quad, coloured and walled from a buffer of per cell state that
+    // only has changed cells written to it
+    cell_pipeline: wgpu::RenderPipeline,
+    corner_buffer: wgpu::Buffer,
+    instance_buffer: wgpu::Buffer,
+    instance_count: u32,
+    uniform_buffer: wgpu::Buffer,
+    cell_bind_group: wgpu::BindGroup,
+}
+
+impl GraphicsContext {
+    // Creating some of the wgpu types requires async code
+    pub async fn new(
+        window: &Window,
+        device: &wgpu::Device,
+        sc_desc: &wgpu::SwapChainDescriptor,
+    ) -> Self {
+        let size = window.inner_size();
+
+        let clear_color = wgpu::Color {
+            r: 0.1,
+            g: 0.2,
+            b: 0.3,
+            a: 1.0,
+        };
+
+        let cell_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Cell Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer {
+                        dynamic: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Cell Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[CellUniforms::default()]),
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+        });
+
+        let cell_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Cell Bind Group"),
+            layout: &cell_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(uniform_buffer.slice(..)),
+            }],
+        });
+
+        let cell_vs_module =
+            device.create_shader_module(wgpu::include_spirv!("../shaders/cell.vert.spv"));
+        let cell_fs_module =
+            device.create_shader_module(wgpu::include_spirv!("../shaders/cell.frag.spv"));
+
+        let cell_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Cell Pipeline Layout"),
+            bind_group_layouts: &[&cell_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let cell_pipeline = create_pipeline(
+            device,
+            "Cell Pipeline",
+            &cell_pipeline_layout,
+            (&cell_vs_module, &cell_fs_module),
+            sc_desc.format,
+            &[corner_desc(), instance_desc()],
+        );
+
+        let corner_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Corner Buffer"),
+            contents: bytemuck::cast_slice(CORNERS),
+            usage: wgpu::BufferUsage::VERTEX,
+        });
+
+        // replaced on the first upload, buffers can't be empty
+        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instance Buffer"),
+            contents: bytemuck::cast_slice(&[Instance::default()]),
+            usage: wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
+        });
+
+        Self {
+            size,
+            clear_color,
+            command_encoder: None,
+            cell_pipeline,
+            corner_buffer,
+            instance_buffer,
+            instance_count: 0,
+            uniform_buffer,
+            cell_bind_group,
+        }
+    }
+
+    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.size = new_size;
+    }
+
+    // writes the changed ranges of instances, or all of them when the grid changed size
+    pub fn upload_instances(
+        &mut self,
+        instances: &[Instance],
+        changed: &[Range<usize>],
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        if instances.len() != self.instance_count as usize {
+            self.instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Instance Buffer"),
+                contents: bytemuck::cast_slice(instances),
+                usage: wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
+            });
+            self.instance_count = instances.len() as u32;
+            return;
+        }
+
+        let stride = std::mem::size_of::<Instance>();
+        for range in changed {
+            queue.write_buffer(
+                &self.instance_buffer,
+                (range.start * stride) as wgpu::BufferAddress,
+                bytemuck::cast_slice(&instances[range.clone()]),
+            );
+        }
+    }
+
+    pub fn set_cell_uniforms(&self, uniforms: &CellUniforms, queue: &wgpu::Queue) {
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[*uniforms]));
+    }
+
+    // clears the frame and draws the cells over it. instances are cell indices, so a range of rows
+    // is all that needs drawing
+    pub fn draw_cells(
+        &mut self,
+        view: &wgpu::TextureView,
+        instances: Range<u32>,
+        device: &wgpu::Device,
+    ) {
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(self.clear_color),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.cell_pipeline);
+        render_pass.set_bind_group(0, &self.cell_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.corner_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        render_pass.draw(0..CORNERS.len() as u32, instances);
+        drop(render_pass);
+
+        self.command_encoder = Some(encoder);
+    }
+
+    pub fn render(&mut self, queue: &wgpu::Queue) {
+        // submit will accept anything that implements IntoIter
+        queue.submit(std::iter::once(
+            self.command_encoder.take().unwrap().finish(),
+        ));
+        self.command_encoder = None;
+    }
+}
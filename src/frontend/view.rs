@@ -0,0 +1,229 @@
+// maps grids to and from normalized device coordinates, the only place cell layout on screen lives
+use crate::grids::Dimensions;
+use std::ops::Range;
+use winit::dpi::PhysicalSize;
+
+// share of each cell given to the gap east and north of it, where wall grids draw their walls
+pub const SQUARE_GAP: f32 = 0.1;
+// thinner walls than this drop out when rasterised
+pub const MIN_GAP_PIXELS: f32 = 1.0;
+// how much of the window a fitted grid covers
+pub const FIT_SCALE: f32 = 0.9;
+// pixels per cell
+pub const MIN_ZOOM: f32 = 0.1;
+pub const MAX_ZOOM: f32 = 400.0;
+
+// grid space puts cell (row, column) in the unit square with its south west corner at
+// (column, row), the camera maps that onto the window with the same scale on both axes so cells
+// stay square whatever the window shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    // grid space point drawn in the middle of the window
+    pub center: (f32, f32),
+    // pixels per cell
+    pub zoom: f32,
+}
+
+impl Camera {
+    // the whole grid, centred
+    pub fn fit(dims: Dimensions, size: PhysicalSize<u32>) -> Self {
+        let zoom = FIT_SCALE
+            * (size.width as f32 / dims.columns as f32).min(size.height as f32 / dims.rows as f32);
+        Self {
+            center: (
+                (dims.columns as f32 - SQUARE_GAP) / 2.0,
+                (dims.rows as f32 - SQUARE_GAP) / 2.0,
+            ),
+            zoom: zoom.max(MIN_ZOOM).min(MAX_ZOOM),
+        }
+    }
+
+    pub fn to_ndc(&self, point: (f32, f32), size: PhysicalSize<u32>) -> [f32; 2] {
+        [
+            (point.0 - self.center.0) * self.zoom * 2.0 / size.width as f32,
+            (point.1 - self.center.1) * self.zoom * 2.0 / size.height as f32,
+        ]
+    }
+
+    // pos is the cursor as a fraction of the window, top left origin
+    pub fn to_grid(&self, pos: (f32, f32), size: PhysicalSize<u32>) -> (f32, f32) {
+        let x = (pos.0 - 0.5) * size.width as f32;
+        let y = (0.5 - pos.1) * size.height as f32;
+        (self.center.0 + x / self.zoom, self.center.1 + y / self.zoom)
+    }
+
+    // share of a cell taken by the gap after it, widened when zoomed out so walls stay visible
+    pub fn gap(&self) -> f32 {
+        (MIN_GAP_PIXELS / self.zoom).max(SQUARE_GAP).min(0.5)
+    }
+
+    // rows and columns with any part inside the window
+    pub fn visible(
+        &self,
+        dims: Dimensions,
+        size: PhysicalSize<u32>,
+    ) -> (Range<usize>, Range<usize>) {
+        let (left, top) = self.to_grid((0.0, 0.0), size);
+        let (right, bottom) = self.to_grid((1.0, 1.0), size);
+        let clamp = |value: f32, max: usize| (value.max(0.0) as usize).min(max);
+        (
+            clamp(bottom, dims.rows)..clamp(top + 1.0, dims.rows),
+            clamp(left, dims.columns)..clamp(right + 1.0, dims.columns),
+        )
+    }
+
+    // drags the grid along with the cursor, delta in pixels with y pointing down
+    pub fn pan(&mut self, delta: (f32, f32)) {
+        self.center.0 -= delta.0 / self.zoom;
+        self.center.1 += delta.1 / self.zoom;
+    }
+
+    // zooms by factor, keeping the grid point under the cursor where it is
+    pub fn zoom_at(&mut self, pos: (f32, f32), size: PhysicalSize<u32>, factor: f32) {
+        let before = self.to_grid(pos, size);
+        self.zoom = (self.zoom * factor).max(MIN_ZOOM).min(MAX_ZOOM);
+        let after = self.to_grid(pos, size);
+        self.center.0 += before.0 - after.0;
+        self.center.1 += before.1 - after.1;
+    }
+}
+
+// the cell under the cursor, gaps count towards the cell south west of them
+pub fn cell_at(
+    dims: Dimensions,
+    camera: &Camera,
+    pos: (f32, f32),
+    size: PhysicalSize<u32>,
+) -> Option<(usize, usize)> {
+    let (x, y) = camera.to_grid(pos, size);
+    if x < 0. || y < 0. {
+        return None;
+    }
+
+    let row = y as usize;
+    let column = x as usize;
+
+    if row < dims.rows && column < dims.columns {
+        Some((row, column))
+    } else {
+        None
+    }
+}
+
+// the wall closest to the cursor, between the cell under it and one of its neighbours
+pub fn edge_at(
+    dims: Dimensions,
+    camera: &Camera,
+    pos: (f32, f32),
+    size: PhysicalSize<u32>,
+) -> Option<((usize, usize), (usize, usize))> {
+    let (row, column) = cell_at(dims, camera, pos, size)?;
+    let (x, y) = camera.to_grid(pos, size);
+
+    // how far across the cell the cursor is, 0 at the south west corner and 1 at the north east
+    let across = (x - column as f32) / (1.0 - camera.gap());
+    let up = (y - row as f32) / (1.0 - camera.gap());
+
+    let sides = [
+        (1.0 - up, row + 1 < dims.rows, (row + 1, column)),
+        (up, row > 0, (row.wrapping_sub(1), column)),
+        (1.0 - across, column + 1 < dims.columns, (row, column + 1)),
+        (across, column > 0, (row, column.wrapping_sub(1))),
+    ];
+    sides
+        .iter()
+        .filter(|(_, exists, _)| *exists)
+        .min_by(|one, two| one.0.partial_cmp(&two.0).unwrap())
+        .map(|(_, _, neighbor)| ((row, column), *neighbor))
+}
+
+#[cfg(test)]
+mod test_view {
+    use super::*;
+    use crate::frontend::instances::{visible_instances, CellUniforms};
+
+    // what cell.vert does with a corner of the unit quad for the cell at index
+    fn corner_ndc(uniforms: &CellUniforms, index: usize, corner: (f32, f32)) -> (f32, f32) {
+        let columns = uniforms.columns as usize;
+        let cell = ((index % columns) as f32, (index / columns) as f32);
+        (
+            (cell.0 + corner.0 - uniforms.center[0]) * uniforms.scale[0],
+            (cell.1 + corner.1 - uniforms.center[1]) * uniforms.scale[1],
+        )
+    }
+
+    #[test]
+    fn clicks_land_on_the_drawn_cell() {
+        let dims = Dimensions {
+            rows: 4,
+            columns: 6,
+        };
+        let size = PhysicalSize::new(800, 600);
+        let camera = Camera::fit(dims, size);
+        let uniforms = CellUniforms::new(&camera, dims, size);
+
+        // row 2, column 5, less the gap east and north of it
+        let inner = 1.0 - uniforms.gap;
+        let low = corner_ndc(&uniforms, 17, (0.0, 0.0));
+        let up = corner_ndc(&uniforms, 17, (inner, inner));
+        // middle of the cell back into window fractions
+        let (x, y) = ((low.0 + up.0) / 2.0, (low.1 + up.1) / 2.0);
+        let pos = ((x + 1.0) / 2.0, (1.0 - y) / 2.0);
+        assert_eq!(cell_at(dims, &camera, pos, size), Some((2, 5)));
+        assert_eq!(cell_at(dims, &camera, (0.0, 0.0), size), None);
+
+        // just inside the west side of that cell picks the wall towards column 4
+        let x = low.0 + (up.0 - low.0) * 0.1;
+        let pos = ((x + 1.0) / 2.0, pos.1);
+        assert_eq!(edge_at(dims, &camera, pos, size), Some(((2, 5), (2, 4))));
+    }
+
+    #[test]
+    fn zooming_keeps_the_cursor_on_its_cell() {
+        let dims = Dimensions {
+            rows: 255,
+            columns: 255,
+        };
+        let size = PhysicalSize::new(1000, 500);
+        let mut camera = Camera::fit(dims, size);
+        // the short side limits a fitted grid, and cells come out square in pixels
+        assert!((camera.zoom * 255.0 - 450.0).abs() < 0.01);
+        let corner = camera.to_ndc((1.0, 1.0), size);
+        let origin = camera.to_ndc((0.0, 0.0), size);
+        let pixels = (
+            (corner[0] - origin[0]) * 500.0,
+            (corner[1] - origin[1]) * 250.0,
+        );
+        assert!((pixels.0 - pixels.1).abs() < 0.001);
+
+        let pos = (0.3, 0.6);
+        let cell = cell_at(dims, &camera, pos, size);
+        camera.zoom_at(pos, size, 20.0);
+        assert_eq!(cell_at(dims, &camera, pos, size), cell);
+        assert!(camera.zoom > 30.0);
+
+        // dragging a cell's width to the right brings the cell west of it under the cursor
+        let (row, column) = cell.unwrap();
+        camera.pan((camera.zoom, 0.0));
+        assert_eq!(cell_at(dims, &camera, pos, size), Some((row, column - 1)));
+    }
+
+    #[test]
+    fn large_grids_only_draw_what_fits() {
+        let dims = Dimensions {
+            rows: 2001,
+            columns: 2001,
+        };
+        let size = PhysicalSize::new(800, 600);
+        let mut camera = Camera::fit(dims, size);
+        assert_eq!(visible_instances(&camera, dims, size), 0..2001 * 2001);
+
+        // zoomed in on the middle, just the rows on screen
+        camera.zoom = 40.0;
+        let (rows, columns) = camera.visible(dims, size);
+        assert_eq!((rows.len(), columns.len()), (16, 21));
+        let instances = visible_instances(&camera, dims, size);
+        assert_eq!(instances.len(), 16 * 2001);
+        assert_eq!(instances.start, rows.start as u32 * 2001);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::grids::block_grid::BlockGrid;
 use crate::grids::wall_grid::WallGrid;
+use crate::error::MazeResult;
 use crate::generators::Generator;
 use rand::prelude::*;
 use crate::grids::{Direction, CellKind, Grid, GridKind};
@@ -8,14 +9,21 @@ pub struct AldousBroder {
     grid: Box<dyn Grid>,
     grid_kind: GridKind,
     visited: Vec<bool>,
-    rng: ThreadRng,
+    // kept alongside visited so finishing doesn't rescan every cell each step
+    unvisited: usize,
+    rng: StdRng,
     current_cell: (usize, usize),
     current_cell_kind: CellKind,
+    changed: Vec<(usize, usize)>,
     pub done: bool,
 }
 
 impl AldousBroder {
     pub fn new(rows: usize, cols: usize, kind: GridKind) -> Self {
+        Self::with_seed(rows, cols, kind, rand::random())
+    }
+
+    pub fn with_seed(rows: usize, cols: usize, kind: GridKind, seed: u64) -> Self {
         let mut grid: Box<dyn Grid> = match kind {
             GridKind::Block => Box::new(BlockGrid::with_dims(rows, cols)),
             GridKind::Wall => Box::new(WallGrid::with_dims(rows, cols)),
@@ -36,30 +44,43 @@ impl AldousBroder {
             GridKind::Wall => vec![false; rows * cols],
         };
 
+        let unvisited = visited.iter().filter(|visited| !**visited).count();
         let current_cell = (1, 1);
         Self {
             grid,
             grid_kind: kind,
             visited,
-            rng: rand::thread_rng(),
+            unvisited,
+            rng: StdRng::seed_from_u64(seed),
             current_cell,
             current_cell_kind: CellKind::Empty,
+            changed: Vec::new(),
             done: false,
         }
     }
+
+    fn visit(&mut self, index: usize) {
+        if !self.visited[index] {
+            self.visited[index] = true;
+            self.unvisited -= 1;
+        }
+    }
 }
 
 
 impl Generator for AldousBroder {
-    fn step_generation(&mut self) {
-            if !self.visited.contains(&false) {
+    fn step_generation(&mut self) -> MazeResult<()> {
+            self.changed.clear();
+            // the cell being left is rewritten whichever way this step goes
+            self.changed.push(self.current_cell);
+            if self.unvisited == 0 {
                 self.done = true;
                 self.grid
                     .set_cell(self.current_cell.0, self.current_cell.1, self.current_cell_kind);
-                return;
+                return Ok(());
             }
             let neighbor_idx = (self.current_cell.0 * self.grid.dims().columns) + self.current_cell.1;
-            self.visited[neighbor_idx] = true;
+            self.visit(neighbor_idx);
             let rand_wall = self.current_cell;
             let neighbors = self.grid.get_neighborhood_of(rand_wall.0, rand_wall.1);
 
@@ -126,25 +147,27 @@ impl Generator for AldousBroder {
                     self.grid.set_cell(self.current_cell.0, self.current_cell.1, self.current_cell_kind);
                     let rand_neighbor_idx = (rand_neighbor.1.0 * self.grid.dims().columns) + rand_neighbor.1.1;
                     if !self.visited[rand_neighbor_idx] {
-                        self.grid.clear_wall_between(self.current_cell, rand_neighbor.1);
+                        self.grid.clear_wall_between(self.current_cell, rand_neighbor.1)?;
                     }
                 }
             }
             self.current_cell = rand_neighbor.1; 
+            self.changed.push(self.current_cell);
             let rand_neighbor_idx = (rand_neighbor.1.0 * self.grid.dims().columns) + rand_neighbor.1.1;
-            self.visited[rand_neighbor_idx] = true;
+            self.visit(rand_neighbor_idx);
             self.grid
                     .set_cell(rand_neighbor.1.0, rand_neighbor.1.1, CellKind::Cursor);
+            Ok(())
     }
 
-    fn next_step(&mut self) -> &dyn Grid {
-        self.step_generation();
-        self.grid.as_ref()
+    fn next_step(&mut self) -> MazeResult<&dyn Grid> {
+        self.step_generation()?;
+        Ok(self.grid.as_ref())
     }
 
-    fn generate_maze(&mut self) -> &dyn Grid {
+    fn generate_maze(&mut self) -> MazeResult<&dyn Grid> {
         loop {
-            self.step_generation();
+            self.step_generation()?;
             if self.done {
                 break;
             }
@@ -152,11 +175,34 @@ impl Generator for AldousBroder {
         self.grid
             .set_cell(self.current_cell.0, self.current_cell.1, self.current_cell_kind);
 
-        self.grid.as_ref()
+        Ok(self.grid.as_ref())
     }
 
 
     fn is_done(&self) -> bool {
         self.done
     }
+
+    fn grid(&self) -> &dyn Grid {
+        self.grid.as_ref()
+    }
+
+    fn changed(&self) -> &[(usize, usize)] {
+        &self.changed
+    }
+}
+
+#[cfg(test)]
+mod test_aldous_broder {
+    use super::*;
+    use crate::generators::bias::measure_bias;
+
+    #[test]
+    fn wall_grid_is_uniform() {
+        let report = measure_bias(3, 3, GridKind::Wall, 192 * 50, |seed| {
+            Box::new(AldousBroder::with_seed(3, 3, GridKind::Wall, seed))
+        });
+
+        assert!(report.is_uniform(), "{:?}", report);
+    }
 }
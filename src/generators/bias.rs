@@ -0,0 +1,214 @@
+// test utilities for checking that a Generator picks spanning trees uniformly at random. run it
+// on a tiny maze, count how often each tree shows up and compare against a flat distribution
+use crate::generators::Generator;
+use crate::grids::{CellKind, Grid, GridKind};
+use std::collections::HashMap;
+
+// z score for a one sided p of 0.001
+pub const Z_999: f64 = 3.090;
+
+// passages between maze cells, in maze cell coordinates so both grid kinds line up. a BlockGrid
+// maze cell (r, c) lives at block (2r + 1, 2c + 1)
+pub type Passages = Vec<((usize, usize), (usize, usize))>;
+
+#[derive(Debug)]
+pub struct BiasReport {
+    pub runs: usize,
+    // generated mazes that were not spanning trees
+    pub invalid: usize,
+    pub trees: usize,
+    pub distinct: usize,
+    pub chi_squared: f64,
+    pub critical: f64,
+}
+
+impl BiasReport {
+    pub fn is_uniform(&self) -> bool {
+        self.invalid == 0 && self.chi_squared < self.critical
+    }
+}
+
+// rows and columns are in maze cells, make builds a generator for that size from a seed
+pub fn measure_bias(
+    rows: usize,
+    columns: usize,
+    kind: GridKind,
+    runs: usize,
+    make: impl Fn(u64) -> Box<dyn Generator>,
+) -> BiasReport {
+    let mut counts: HashMap<Passages, usize> = HashMap::new();
+    let mut invalid = 0;
+
+    for seed in 0..runs as u64 {
+        let mut generator = make(seed);
+        let passages = passages_of(generator.generate_maze().unwrap(), kind, rows, columns);
+
+        if is_spanning_tree(&passages, rows, columns) {
+            *counts.entry(passages).or_insert(0) += 1;
+        } else {
+            invalid += 1;
+        }
+    }
+
+    let trees = spanning_tree_count(rows, columns).round() as usize;
+    let chi_squared = chi_squared(&counts, trees);
+    let critical = chi_squared_critical((trees - 1) as f64, Z_999);
+
+    BiasReport {
+        runs,
+        invalid,
+        trees,
+        distinct: counts.len(),
+        chi_squared,
+        critical,
+    }
+}
+
+pub fn passages_of(grid: &dyn Grid, kind: GridKind, rows: usize, columns: usize) -> Passages {
+    let is_open = |one: (usize, usize), two: (usize, usize)| match kind {
+        GridKind::Wall => grid.is_passable(one, two),
+        GridKind::Block => grid.get_cell(one.0 + two.0 + 1, one.1 + two.1 + 1) != CellKind::Wall,
+    };
+
+    let mut passages = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            if column + 1 < columns && is_open((row, column), (row, column + 1)) {
+                passages.push(((row, column), (row, column + 1)));
+            }
+            if row + 1 < rows && is_open((row, column), (row + 1, column)) {
+                passages.push(((row, column), (row + 1, column)));
+            }
+        }
+    }
+
+    passages
+}
+
+pub fn is_spanning_tree(passages: &Passages, rows: usize, columns: usize) -> bool {
+    if passages.len() + 1 != rows * columns {
+        return false;
+    }
+
+    // n - 1 edges and connected means tree
+    let mut seen = vec![false; rows * columns];
+    let mut stack = vec![(0, 0)];
+    seen[0] = true;
+    while let Some(cell) = stack.pop() {
+        for (one, two) in passages {
+            let next = if *one == cell {
+                *two
+            } else if *two == cell {
+                *one
+            } else {
+                continue;
+            };
+
+            if !seen[(next.0 * columns) + next.1] {
+                seen[(next.0 * columns) + next.1] = true;
+                stack.push(next);
+            }
+        }
+    }
+
+    !seen.contains(&false)
+}
+
+// kirchhoff's matrix tree theorem, determinant of the grid laplacian with one row and column cut
+pub fn spanning_tree_count(rows: usize, columns: usize) -> f64 {
+    let size = rows * columns - 1;
+    let mut matrix = vec![vec![0.0f64; size]; size];
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let index = (row * columns) + column;
+            let mut neighbors = Vec::new();
+            if row > 0 {
+                neighbors.push(index - columns);
+            }
+            if row + 1 < rows {
+                neighbors.push(index + columns);
+            }
+            if column > 0 {
+                neighbors.push(index - 1);
+            }
+            if column + 1 < columns {
+                neighbors.push(index + 1);
+            }
+
+            // drop node 0
+            if index == 0 {
+                continue;
+            }
+            matrix[index - 1][index - 1] = neighbors.len() as f64;
+            for neighbor in neighbors.into_iter().filter(|n| *n != 0) {
+                matrix[index - 1][neighbor - 1] = -1.0;
+            }
+        }
+    }
+
+    let mut determinant = 1.0;
+    for pivot in 0..size {
+        let best = (pivot..size)
+            .max_by(|one, two| {
+                matrix[*one][pivot]
+                    .abs()
+                    .partial_cmp(&matrix[*two][pivot].abs())
+                    .unwrap()
+            })
+            .unwrap();
+        if matrix[best][pivot] == 0.0 {
+            return 0.0;
+        }
+        if best != pivot {
+            matrix.swap(best, pivot);
+            determinant = -determinant;
+        }
+
+        determinant *= matrix[pivot][pivot];
+        for row in pivot + 1..size {
+            let factor = matrix[row][pivot] / matrix[pivot][pivot];
+            for column in pivot..size {
+                matrix[row][column] -= factor * matrix[pivot][column];
+            }
+        }
+    }
+
+    determinant
+}
+
+// trees that never showed up still count against the statistic
+pub fn chi_squared(counts: &HashMap<Passages, usize>, categories: usize) -> f64 {
+    let total: usize = counts.values().sum();
+    let expected = total as f64 / categories as f64;
+    let missing = categories.saturating_sub(counts.len()) as f64;
+
+    counts
+        .values()
+        .map(|observed| (*observed as f64 - expected).powi(2) / expected)
+        .sum::<f64>()
+        + missing * expected
+}
+
+// wilson-hilferty approximation of the chi squared quantile
+pub fn chi_squared_critical(degrees: f64, z: f64) -> f64 {
+    let spread = 2.0 / (9.0 * degrees);
+    degrees * (1.0 - spread + z * spread.sqrt()).powi(3)
+}
+
+#[cfg(test)]
+mod test_bias {
+    use super::*;
+
+    #[test]
+    fn counts_spanning_trees() {
+        assert_eq!(spanning_tree_count(2, 2).round(), 4.0);
+        assert_eq!(spanning_tree_count(3, 3).round(), 192.0);
+    }
+
+    #[test]
+    fn critical_value_is_close() {
+        // tables give 149.449 for 100 degrees at p = 0.001
+        assert!((chi_squared_critical(100.0, Z_999) - 149.449).abs() < 0.5);
+    }
+}
@@ -1,17 +1,105 @@
 pub mod aldous_broder;
+#[cfg(test)]
+pub mod bias;
 pub mod division;
 pub mod prim;
-use crate::grids::Grid;
+use crate::error::MazeResult;
+use crate::grids::{new_grid, Grid, GridKind};
+use aldous_broder::AldousBroder;
+use prim::RandPrims;
+use serde::{Deserialize, Serialize};
 
 pub trait Generator {
-    fn step_generation(&mut self);
-    fn next_step(&mut self) -> &dyn Grid;
-    fn generate_maze(&mut self) -> &dyn Grid;
+    fn step_generation(&mut self) -> MazeResult<()>;
+    fn next_step(&mut self) -> MazeResult<&dyn Grid>;
+    fn generate_maze(&mut self) -> MazeResult<&dyn Grid>;
     fn is_done(&self) -> bool;
+    // the maze as it stands
+    fn grid(&self) -> &dyn Grid;
+    // cells the last step set or cleared a wall beside
+    fn changed(&self) -> &[(usize, usize)];
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum GeneratorKind {
     AldousBroder,
     RandPrims,
 }
+
+pub fn new_generator(
+    generator_kind: GeneratorKind,
+    rows: usize,
+    columns: usize,
+    grid_kind: GridKind,
+    seed: u64,
+) -> Box<dyn Generator> {
+    match generator_kind {
+        GeneratorKind::AldousBroder => {
+            Box::new(AldousBroder::with_seed(rows, columns, grid_kind, seed))
+        }
+        GeneratorKind::RandPrims => Box::new(RandPrims::with_seed(rows, columns, grid_kind, seed)),
+    }
+}
+
+// runs a generator to the end and hands back its maze as a grid of our own
+pub fn generate_grid(
+    generator_kind: GeneratorKind,
+    rows: usize,
+    columns: usize,
+    grid_kind: GridKind,
+    seed: u64,
+) -> MazeResult<Box<dyn Grid>> {
+    let mut generator = new_generator(generator_kind, rows, columns, grid_kind, seed);
+    let generated = generator.generate_maze()?;
+
+    let mut grid = new_grid(grid_kind, rows, columns);
+    grid.set_cells(generated.cells().clone());
+    grid.set_paths(generated.paths()?)?;
+
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod test_generators {
+    use super::*;
+    use crate::grids::CellKind;
+
+    // copying only what each step reports has to end up with the same maze as copying everything
+    #[test]
+    fn steps_report_everything_they_change() {
+        for &grid_kind in &[GridKind::Block, GridKind::Wall] {
+            for &generator_kind in &[GeneratorKind::AldousBroder, GeneratorKind::RandPrims] {
+                let mut generator = new_generator(generator_kind, 9, 9, grid_kind, 7);
+                let mut copy = new_grid(grid_kind, 9, 9);
+                copy.set_cells(generator.grid().cells().clone());
+                copy.set_paths(generator.grid().paths().unwrap()).unwrap();
+
+                while !generator.is_done() {
+                    generator.step_generation().unwrap();
+                    let generated = generator.grid();
+                    for &(row, column) in generator.changed() {
+                        copy.set_cell(row, column, generated.get_cell(row, column));
+                        // block grid walls are cells, wall grids keep theirs apart
+                        if grid_kind != GridKind::Wall {
+                            continue;
+                        }
+                        let neighbors = [
+                            (row + 1, column),
+                            (row.wrapping_sub(1), column),
+                            (row, column + 1),
+                            (row, column.wrapping_sub(1)),
+                        ];
+                        for &neighbor in neighbors.iter().filter(|cell| cell.0 < 9 && cell.1 < 9) {
+                            if generated.is_passable((row, column), neighbor) {
+                                copy.clear_wall_between((row, column), neighbor).unwrap();
+                            }
+                        }
+                    }
+                    assert_eq!(copy.cells(), generated.cells());
+                }
+                assert_eq!(copy.paths(), generator.grid().paths());
+                assert!(copy.cells().iter().all(|cell| *cell != CellKind::Cursor));
+            }
+        }
+    }
+}
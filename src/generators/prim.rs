@@ -1,5 +1,6 @@
 use crate::grids::block_grid::BlockGrid;
 use crate::grids::wall_grid::WallGrid;
+use crate::error::MazeResult;
 use crate::generators::Generator;
 use rand::prelude::*;
 use crate::grids::{CellKind, Grid, GridKind};
@@ -8,19 +9,24 @@ pub struct RandPrims {
     grid: Box<dyn Grid>,
     grid_kind: GridKind,
     walls: Vec<(usize, usize)>,
-    rng: ThreadRng,
+    rng: StdRng,
     last_passage: (usize, usize),
+    changed: Vec<(usize, usize)>,
     pub done: bool
 }
 
 impl RandPrims {
     pub fn new(rows: usize, cols: usize, kind: GridKind) -> Self {
+        Self::with_seed(rows, cols, kind, rand::random())
+    }
+
+    pub fn with_seed(rows: usize, cols: usize, kind: GridKind, seed: u64) -> Self {
         let mut grid: Box<dyn Grid> = match kind {
             GridKind::Block => Box::new(BlockGrid::with_dims(rows, cols)),
             GridKind::Wall => Box::new(WallGrid::with_dims(rows, cols)),
         };
         grid.fill();
-        let mut rng = rand::thread_rng();
+        let mut rng = StdRng::seed_from_u64(seed);
         // make it odd
         let row = (rng.gen::<f32>() * (rows - 1) as f32) as usize | 1;
         let col = (rng.gen::<f32>() * (cols - 1) as f32) as usize | 1;
@@ -32,22 +38,26 @@ impl RandPrims {
             walls,
             last_passage,
             rng,
+            changed: Vec::new(),
             done: false,
         }
     }
 }
 
 impl Generator for RandPrims {
-    fn step_generation(&mut self) {
+    fn step_generation(&mut self) -> MazeResult<()> {
+        self.changed.clear();
         // loop until wall is found
         loop {
             if self.walls.len() == 0 {
                 self.done = true;
                 self.grid.set_cell(self.last_passage.0, self.last_passage.1, CellKind::Empty);
+                self.changed.push(self.last_passage);
                 break;
             }
             let rand_wall_idx = (self.walls.len() as f32 * self.rng.gen::<f32>()) as usize;
-            let rand_wall = self.walls.remove(rand_wall_idx);
+            // order doesn't matter since walls are picked at random, and remove is linear
+            let rand_wall = self.walls.swap_remove(rand_wall_idx);
             let neighbors = self.grid.get_neighborhood_of(rand_wall.0, rand_wall.1);
 
 
@@ -64,9 +74,12 @@ impl Generator for RandPrims {
                 self.grid.set_cell(self.last_passage.0, self.last_passage.1, CellKind::Empty);
                 self.grid
                     .set_cell(rand_wall.0, rand_wall.1, CellKind::Empty);
+                self.changed.push(self.last_passage);
+                self.changed.push(rand_wall);
 
                 if let Some(dir) = unwalled_dir {
                     self.last_passage = self.grid.set_neighbor_of(rand_wall, -dir, CellKind::Cursor);
+                    self.changed.push(self.last_passage);
                 }
                 let mut walls_to_add = Vec::new();
                 for (neighbor, _) in self.grid.get_neighborhood_of(self.last_passage.0, self.last_passage.1) {
@@ -83,26 +96,51 @@ impl Generator for RandPrims {
                 break;
             }
         }
+        Ok(())
     }
 
-    fn next_step(&mut self) ->  &dyn Grid {
-        self.step_generation();
-        self.grid.as_ref()
+    fn next_step(&mut self) ->  MazeResult<&dyn Grid> {
+        self.step_generation()?;
+        Ok(self.grid.as_ref())
     }
 
-    fn generate_maze(&mut self) -> &dyn Grid{
+    fn generate_maze(&mut self) -> MazeResult<&dyn Grid>{
         loop {
-            self.step_generation();
+            self.step_generation()?;
             if self.done {
                 break;
             }
         }
         self.grid.set_cell(self.last_passage.0, self.last_passage.1, CellKind::Empty);
 
-        self.grid.as_ref()
+        Ok(self.grid.as_ref())
     }
 
     fn is_done(&self) -> bool {
         self.done
     }
+
+    fn grid(&self) -> &dyn Grid {
+        self.grid.as_ref()
+    }
+
+    fn changed(&self) -> &[(usize, usize)] {
+        &self.changed
+    }
+}
+
+#[cfg(test)]
+mod test_prims {
+    use super::*;
+    use crate::generators::bias::measure_bias;
+
+    #[test]
+    fn block_grid_is_biased() {
+        let report = measure_bias(3, 3, GridKind::Block, 192 * 50, |seed| {
+            Box::new(RandPrims::with_seed(7, 7, GridKind::Block, seed))
+        });
+
+        assert_eq!(report.invalid, 0);
+        assert!(!report.is_uniform(), "{:?}", report);
+    }
 }
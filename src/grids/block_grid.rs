@@ -1,11 +1,6 @@
 const DEFAULT_DIMS: (usize, usize) = (16, 16);
 
-pub const GRID_SCALE: f32 = 1.3;
-pub const SQUARE_GAP: f32 = 0.005;
-
-use crate::renderer::Vertex;
-use crate::State;
-
+use crate::error::{MazeError, MazeResult};
 use crate::grids::{CellKind, Dimensions, Direction, Grid, Neighborhood, SolverKind};
 use bit_graph::search::a_star::AStarMH;
 use bit_graph::search::bfs::BFS;
@@ -13,6 +8,7 @@ use bit_graph::search::dfs::DFS;
 use bit_graph::search::Pathfinder;
 use bit_graph::BitGraph;
 use bit_graph::Graph;
+use std::collections::HashSet;
 
 pub struct BlockGrid {
     pub dims: Dimensions,
@@ -21,6 +17,9 @@ pub struct BlockGrid {
 
     pub start: Option<(usize, usize)>,
     pub goal: Option<(usize, usize)>,
+    // every placed goal, `goal` is always the first of these
+    pub goals: Vec<(usize, usize)>,
+    pub multi_goal: bool,
     pub cursor: Option<(usize, usize)>,
 
     pub graph: Option<Box<dyn Graph<u64, bool>>>,
@@ -39,6 +38,8 @@ impl BlockGrid {
             dims: Dimensions { rows, columns },
             start: None,
             goal: None,
+            goals: Vec::new(),
+            multi_goal: false,
             cursor: None,
             graph: None,
             solver: None,
@@ -59,61 +60,78 @@ impl BlockGrid {
         self.set_cell(row, column, CellKind::Empty)
     }
 
-    fn get_ndc_params(&self, size: winit::dpi::PhysicalSize<u32>) -> (f32, f32, f32, f32) {
-        let ratio = size.width as f32 / size.height as f32;
-        let (sq_width, sq_height) = if ratio >= 1.0 {
-            (
-                GRID_SCALE / self.dims.columns as f32 / ratio,
-                GRID_SCALE / self.dims.rows as f32,
-            )
-        } else {
-            (
-                GRID_SCALE / self.dims.columns as f32,
-                GRID_SCALE / self.dims.rows as f32 * ratio,
-            )
-        };
-
-        // centers the grid somehow, this will need some additional calculations to detect large
-        // gaps, but good enough for now to buy space
-        let bottom_left_x =
-            (2.0 - (GRID_SCALE + (self.dims.columns as f32 * SQUARE_GAP))) / 2.0 - 1.0;
-        let bottom_left_y = (2.0 - (GRID_SCALE + (self.dims.rows as f32 * SQUARE_GAP))) / 2.0 - 1.0;
+    // clears an old start or goal, unless something else has been placed over it since
+    fn unset_end(&mut self, (row, column): (usize, usize), kind: CellKind) {
+        if self.get_cell(row, column) == kind {
+            self.unset_cell(row, column);
+        }
+    }
 
-        (sq_width, sq_height, bottom_left_x, bottom_left_y)
+    // maze cells sit on odd coordinates with a block standing in for each wall between them,
+    // these are the dimensions of the wall grid that describes
+    pub fn lattice_dims(&self) -> Dimensions {
+        Dimensions {
+            rows: self.dims.rows.saturating_sub(1) / 2,
+            columns: self.dims.columns.saturating_sub(1) / 2,
+        }
     }
 
-    pub fn toggle_cell(&mut self, row: usize, column: usize, kind: CellKind) -> CellKind {
-        let word_row = self.dims.columns * row;
-        let word_col = column;
-        let prev_kind = self.cells[word_row + word_col];
+    // block holding the wall between two maze cells, which sit two apart
+    fn wall_cell_between(
+        &self,
+        one: (usize, usize),
+        two: (usize, usize),
+    ) -> MazeResult<(usize, usize)> {
+        for cell in &[one, two] {
+            if cell.0 >= self.dims.rows || cell.1 >= self.dims.columns {
+                return Err(MazeError::OutOfBounds(*cell));
+            }
+        }
 
-        if prev_kind == CellKind::Empty {
-            self.cells[word_row + word_col] = kind;
-        } else if prev_kind != kind {
-            self.cells[word_row + word_col] = kind;
-        } else {
-            self.cells[word_row + word_col] = CellKind::Empty;
+        let lattice = self.lattice_dims();
+        let on_lattice = |cell: (usize, usize)| {
+            cell.0 % 2 == 1
+                && cell.1 % 2 == 1
+                && cell.0 < 2 * lattice.rows
+                && cell.1 < 2 * lattice.columns
+        };
+        // two odd coordinates can only be two apart along a single axis
+        let distance = one.0.max(two.0) - one.0.min(two.0) + one.1.max(two.1) - one.1.min(two.1);
+        if !on_lattice(one) || !on_lattice(two) || distance != 2 {
+            return Err(MazeError::NotAdjacent(one, two));
         }
 
-        if kind == CellKind::Start {
-            if let Some(start) = self.start {
-                self.unset_cell(start.0, start.1);
-            }
-            if self.cells[word_row + word_col] == CellKind::Start {
-                self.start = Some((row, column));
-            }
+        Ok(((one.0 + two.0) / 2, (one.1 + two.1) / 2))
+    }
+
+    // walls a block, letting go of any start or goal that was on it
+    fn wall_off(&mut self, cell: (usize, usize)) {
+        self.set_cell(cell.0, cell.1, CellKind::Wall);
+        if self.start == Some(cell) {
+            self.start = None;
         }
+        if self.goals.contains(&cell) {
+            self.goals.retain(|goal| *goal != cell);
+            self.goal = self.goals.first().cloned();
+        }
+    }
 
-        if kind == CellKind::Goal {
-            if let Some(goal) = self.goal {
-                self.unset_cell(goal.0, goal.1);
-            }
-            if self.cells[word_row + word_col] == CellKind::Goal {
-                self.goal = Some((row, column));
+    // every block that can hold a wall between two maze cells
+    fn wall_cells(&self) -> Vec<(usize, usize)> {
+        let lattice = self.lattice_dims();
+        let mut cells = Vec::new();
+        for row in 0..lattice.rows {
+            for column in 0..lattice.columns {
+                let (block_row, block_column) = ((2 * row) + 1, (2 * column) + 1);
+                if column + 1 < lattice.columns {
+                    cells.push((block_row, block_column + 1));
+                }
+                if row + 1 < lattice.rows {
+                    cells.push((block_row + 1, block_column));
+                }
             }
         }
-
-        prev_kind
+        cells
     }
 
     // returns coords of neighbor
@@ -136,7 +154,14 @@ impl BlockGrid {
         (n_row, n_col)
     }
 
-    pub fn make_graph(&mut self) {
+    pub fn make_graph(&mut self) -> MazeResult<()> {
+        let root = self.start.ok_or(MazeError::NoStart)?;
+        let goal = match self.goal {
+            Some(inner) => inner,
+            // astar needs somewhere to aim, the others only use the goal to stop
+            None if self.solver_kind == SolverKind::AStar => return Err(MazeError::NoGoal),
+            None => (0, 0),
+        };
         let mut graph = BitGraph::with_capacity(self.dims.rows * self.dims.columns);
 
         for _ in &self.cells {
@@ -151,7 +176,10 @@ impl BlockGrid {
             let row = i / self.dims.columns;
             let col = i % self.dims.columns;
 
-            if square == &CellKind::Path || square == &CellKind::Explored {
+            if square == &CellKind::Path
+                || square == &CellKind::Explored
+                || square == &CellKind::Cursor
+            {
                 self.set_cell(row, col, CellKind::Empty);
             }
 
@@ -173,26 +201,19 @@ impl BlockGrid {
             }
         }
 
-        self.graph = Some(Box::new(graph));
-        let graph = &**self.graph.as_ref().unwrap();
-        let root = self.start.unwrap();
+        let graph: Box<dyn Graph<u64, bool>> = Box::new(graph);
         let index = (self.dims.columns * root.0) + root.1;
-        let goal = match self.goal {
-            Some(inner) => inner,
-            None => {
-                if self.solver_kind == SolverKind::AStar {
-                    panic!("Astar requires a goal")
-                } else {
-                    (0, 0)
-                }
-            }
-        };
         let goal_idx = (self.dims.columns * goal.0) + goal.1;
         self.solver = Some(match self.solver_kind {
-            SolverKind::BFS => Box::new(BFS::new(graph, index)),
-            SolverKind::DFS => Box::new(DFS::new(graph, index)),
-            SolverKind::AStar => Box::new(AStarMH::new(graph, index, goal_idx, self.dims.columns)),
+            SolverKind::BFS => Box::new(BFS::new(&*graph, index)),
+            SolverKind::DFS => Box::new(DFS::new(&*graph, index)),
+            SolverKind::AStar => {
+                Box::new(AStarMH::new(&*graph, index, goal_idx, self.dims.columns))
+            }
         });
+        self.graph = Some(graph);
+        self.cursor = None;
+        Ok(())
     }
 }
 
@@ -263,154 +284,109 @@ impl Grid for BlockGrid {
 
         prev_kind
     }
+
+    fn toggle_cell(&mut self, row: usize, column: usize, kind: CellKind) -> CellKind {
+        let word_row = self.dims.columns * row;
+        let word_col = column;
+        let prev_kind = self.cells[word_row + word_col];
+
+        if prev_kind == CellKind::Empty {
+            self.cells[word_row + word_col] = kind;
+        } else if prev_kind != kind {
+            self.cells[word_row + word_col] = kind;
+        } else {
+            self.cells[word_row + word_col] = CellKind::Empty;
+        }
+
+        if kind == CellKind::Start {
+            if let Some(start) = self.start {
+                self.unset_cell(start.0, start.1);
+            }
+            if self.cells[word_row + word_col] == CellKind::Start {
+                self.start = Some((row, column));
+            }
+        }
+
+        if kind == CellKind::Goal || prev_kind == CellKind::Goal {
+            if kind == CellKind::Goal && !self.multi_goal {
+                for goal in std::mem::take(&mut self.goals) {
+                    if goal != (row, column) {
+                        self.unset_cell(goal.0, goal.1);
+                    }
+                }
+            }
+            self.goals.retain(|goal| *goal != (row, column));
+            if self.cells[word_row + word_col] == CellKind::Goal {
+                self.goals.push((row, column));
+            }
+            self.goal = self.goals.first().cloned();
+        }
+
+        prev_kind
+    }
+
     fn clear(&mut self) {
         self.cells = vec![CellKind::Empty; self.cells.len()];
         self.start = None;
         self.goal = None;
+        self.goals.clear();
         self.cursor = None;
-        self.reset_solver();
+        self.graph = None;
     }
 
     fn fill(&mut self) {
         self.cells = vec![CellKind::Wall; self.cells.len()];
         self.start = None;
         self.goal = None;
+        self.goals.clear();
         self.cursor = None;
     }
-    fn handle_click(
-        &mut self,
-        pos: (f32, f32),
-        size: winit::dpi::PhysicalSize<u32>,
-        kind: CellKind,
-    ) {
-        let x = (2.0 * pos.0) - 1.0;
-        let y = (2.0 * pos.1) - 1.0;
-        let y = -y;
-
-        let (sq_width, sq_height, bottom_left_x, bottom_left_y) = self.get_ndc_params(size);
-
-        let (row, column) = {
-            let x = x - bottom_left_x;
-            let y = y - bottom_left_y;
-
-            if x < 0. || y < 0. {
-                return;
-            }
-
-            (
-                (y / (sq_height + SQUARE_GAP)) as usize,
-                (x / (sq_width + SQUARE_GAP)) as usize,
-            )
-        };
-
-        if row < self.dims.rows && column < self.dims.columns {
-            self.toggle_cell(row, column, kind);
-        }
-    }
-    fn render(&self, state: &State) -> Vec<Vertex> {
-        let mut grid = Vec::new();
-        let (sq_width, sq_height, center_x, center_y) = self.get_ndc_params(state.gfx_ctx.size);
-
-        let mut offset_x = center_x;
-        let mut offset_y = center_y;
-
-        //@TODO factor in GRID_SCALE somehow so that the grid has a margin from the border of the
-        //screen, make boxes touch?
-        for row in 0..self.dims.rows {
-            for col in 0..self.dims.columns {
-                let low_x = offset_x;
-                let low_y = offset_y;
-
-                let up_x = low_x + sq_width;
-                let up_y = low_y + sq_height;
-
-                let color: [f32; 4] = self.get_cell(row, col).into();
-
-                let verts: &[Vertex] = &[
-                    // lower left triangle
-                    Vertex {
-                        position: [low_x, low_y],
-                        color,
-                    },
-                    Vertex {
-                        position: [up_x, low_y],
-                        color,
-                    },
-                    Vertex {
-                        position: [low_x, up_y],
-                        color,
-                    },
-                    // upper right triangle
-                    Vertex {
-                        position: [low_x, up_y],
-                        color,
-                    },
-                    Vertex {
-                        position: [up_x, low_y],
-                        color,
-                    },
-                    Vertex {
-                        position: [up_x, up_y],
-                        color,
-                    },
-                ];
-
-                grid.append(&mut Vec::from(verts));
-
-                offset_x += sq_width + SQUARE_GAP;
-            }
-            offset_y += sq_height + SQUARE_GAP;
-            offset_x = center_x;
-        }
-
-        grid
-    }
-
-    fn step_solve_path(&mut self) -> bool {
-        if self.start.is_none() || self.goal.is_none() {
-            return false;
-        }
+    fn step_solve_path(&mut self, changed: &mut Vec<usize>) -> MazeResult<bool> {
+        let start = self.start.ok_or(MazeError::NoStart)?;
+        let goal = self.goal.ok_or(MazeError::NoGoal)?;
 
         if let Some(solver) = self.solver.as_ref() {
             if solver.is_solved() && self.cursor.is_none() {
-                self.reset_solver();
+                self.reset_solver()?;
             }
         }
 
-        let start = self.start.unwrap();
-        let goal = self.goal.unwrap();
         self.set_cell(start.0, start.1, CellKind::Start);
         self.set_cell(goal.0, goal.1, CellKind::Goal);
+        changed.push((start.0 * self.dims.columns) + start.1);
+        changed.push((goal.0 * self.dims.columns) + goal.1);
 
-        let mut index = 0;
-        loop {
+        // the cell explored last step is only the cursor until this one
+        if let Some((row, col)) = self.cursor {
+            let index = (row * self.dims.columns) + col;
             if self.cells[index] == CellKind::Cursor {
                 self.cells[index] = CellKind::Explored;
-                break;
-            }
-            index += 1;
-
-            if index >= self.cells.len() {
-                break;
+                changed.push(index);
             }
         }
 
         if self.graph.is_none() {
-            self.make_graph();
+            changed.extend(super::solver_marks(&self.cells));
+            self.make_graph()?;
         }
 
-        let solver = self.solver.as_mut().unwrap();
-        let graph = &**self.graph.as_ref().unwrap();
+        let (solver, graph) = match (self.solver.as_mut(), self.graph.as_ref()) {
+            (Some(solver), Some(graph)) => (solver, &**graph),
+            _ => return Ok(false),
+        };
 
         let (row, col, kind) = if solver.is_solved() {
-            let cursor = self.cursor.unwrap();
+            let cursor = match self.cursor {
+                Some(cursor) => cursor,
+                None => return Ok(false),
+            };
             let idx = (cursor.0 * self.dims.columns) + cursor.1;
             let from = solver.from_index_of(idx);
             let row = from / self.dims.columns;
             let col = from % self.dims.columns;
 
             if row == start.0 && col == start.1 {
-                return false;
+                return Ok(false);
             }
 
             self.cursor = Some((row, col));
@@ -423,12 +399,12 @@ impl Grid for BlockGrid {
 
                 if row == goal.0 && col == goal.1 {
                     solver.set_solved();
-                    self.cursor = Some((row, col));
                 }
+                self.cursor = Some((row, col));
 
                 (row, col, CellKind::Cursor)
             } else {
-                return false;
+                return Ok(false);
             };
 
             (row, col, kind)
@@ -436,27 +412,27 @@ impl Grid for BlockGrid {
         drop(solver);
 
         self.set_cell(row, col, kind);
-        true
+        changed.push((row * self.dims.columns) + col);
+        Ok(true)
     }
 
-    fn solve_path(&mut self) {
-        if self.start.is_none() || self.goal.is_none() {
-            return;
-        }
+    fn solve_path(&mut self) -> MazeResult<()> {
+        let start = self.start.ok_or(MazeError::NoStart)?;
+        let goal = self.goal.ok_or(MazeError::NoGoal)?;
 
-        self.make_graph();
+        self.make_graph()?;
 
-        let start = self.start.unwrap();
-        let goal = self.goal.unwrap();
         let root_idx = (start.0 * self.dims.columns) + start.1;
         let goal_idx = (goal.0 * self.dims.columns) + goal.1;
-        println!("start: {}, goal: {}", root_idx, goal_idx);
-        let graph = &**self.graph.as_ref().unwrap();
-        let mut solver = self.solver.take().unwrap();
+        log::debug!("start: {}, goal: {}", root_idx, goal_idx);
+        let (mut solver, graph) = match (self.solver.take(), self.graph.as_ref()) {
+            (Some(solver), Some(graph)) => (solver, &**graph),
+            _ => return Ok(()),
+        };
 
         if let Some(path) = solver.path_to(graph, goal_idx) {
             // pop off root
-            println!("Path found: {:?}", path);
+            log::debug!("Path found: {:?}", path);
 
             for i in 1..path.len() - 1 {
                 let row = path[i] / self.dims.columns;
@@ -465,11 +441,12 @@ impl Grid for BlockGrid {
                 self.set_cell(row, col, CellKind::Path);
             }
         } else {
-            println!("path not found");
+            log::debug!("path not found");
         }
 
         // clear graph for reasons
         self.graph = None;
+        Ok(())
     }
 
     fn cells(&self) -> &Vec<CellKind> {
@@ -486,27 +463,125 @@ impl Grid for BlockGrid {
 
     fn set_solver_kind(&mut self, kind: SolverKind) {
         self.solver_kind = kind;
-        self.reset_solver();
+        self.graph = None;
     }
 
-    fn reset_solver(&mut self) {
+    // the graph and solver are rebuilt lazily by the next step or solve
+    fn reset_solver(&mut self) -> MazeResult<()> {
         self.graph = None;
+        Ok(())
     }
 
-    fn add_wall_between(&mut self, _one: (usize, usize), _two: (usize, usize)) {
-        todo!()
+    fn add_wall_between(&mut self, one: (usize, usize), two: (usize, usize)) -> MazeResult<()> {
+        let wall = self.wall_cell_between(one, two)?;
+        self.wall_off(wall);
+        Ok(())
     }
 
-    fn clear_wall_between(&mut self, _one: (usize, usize), _two: (usize, usize)) {
-        todo!()
+    fn clear_wall_between(&mut self, one: (usize, usize), two: (usize, usize)) -> MazeResult<()> {
+        let wall = self.wall_cell_between(one, two)?;
+        if self.get_cell(wall.0, wall.1) == CellKind::Wall {
+            self.set_cell(wall.0, wall.1, CellKind::Empty);
+        }
+        Ok(())
     }
 
-    fn paths(&self) -> Vec<(usize, usize)> {
-        todo!()
+    // passages between maze cells, numbered like the equivalent wall grid so paths carry over
+    // between the two unchanged
+    fn paths(&self) -> MazeResult<Vec<(usize, usize)>> {
+        let lattice = self.lattice_dims();
+        let mut paths = Vec::new();
+        for row in 0..lattice.rows {
+            for column in 0..lattice.columns {
+                let index = (row * lattice.columns) + column;
+                let (block_row, block_column) = ((2 * row) + 1, (2 * column) + 1);
+
+                if column + 1 < lattice.columns
+                    && self.get_cell(block_row, block_column + 1) != CellKind::Wall
+                {
+                    paths.push((index, index + 1));
+                    paths.push((index + 1, index));
+                }
+                if row + 1 < lattice.rows
+                    && self.get_cell(block_row + 1, block_column) != CellKind::Wall
+                {
+                    paths.push((index, index + lattice.columns));
+                    paths.push((index + lattice.columns, index));
+                }
+            }
+        }
+
+        Ok(paths)
+    }
+
+    fn set_paths(&mut self, paths: Vec<(usize, usize)>) -> MazeResult<()> {
+        let lattice = self.lattice_dims();
+        let block_of = |index: usize| {
+            let columns = lattice.columns.max(1);
+            ((2 * (index / columns)) + 1, (2 * (index % columns)) + 1)
+        };
+
+        let mut open = HashSet::new();
+        for (from, to) in paths {
+            open.insert(self.wall_cell_between(block_of(from), block_of(to))?);
+        }
+
+        for cell in self.wall_cells() {
+            if !open.contains(&cell) {
+                self.wall_off(cell);
+            } else if self.get_cell(cell.0, cell.1) == CellKind::Wall {
+                self.set_cell(cell.0, cell.1, CellKind::Empty);
+            }
+        }
+        Ok(())
+    }
+
+    fn is_passable(&self, one: (usize, usize), two: (usize, usize)) -> bool {
+        self.get_cell(one.0, one.1) != CellKind::Wall
+            && self.get_cell(two.0, two.1) != CellKind::Wall
+    }
+
+    fn start(&self) -> Option<(usize, usize)> {
+        self.start
+    }
+
+    fn set_start(&mut self, start: Option<(usize, usize)>) {
+        if let Some(old) = self.start.take() {
+            self.unset_end(old, CellKind::Start);
+        }
+        if let Some(start) = start {
+            self.set_cell(start.0, start.1, CellKind::Start);
+        }
+        self.start = start;
+    }
+
+    fn goals(&self) -> Vec<(usize, usize)> {
+        self.goals.clone()
+    }
+
+    fn set_goal(&mut self, goal: Option<(usize, usize)>) {
+        for old in std::mem::take(&mut self.goals) {
+            self.unset_end(old, CellKind::Goal);
+        }
+        if let Some(goal) = goal {
+            self.set_cell(goal.0, goal.1, CellKind::Goal);
+            self.goals.push(goal);
+        }
+        self.goal = goal;
     }
 
-    fn set_paths(&mut self, _paths: Vec<(usize, usize)>) {
-        todo!()
+    fn multi_goal(&self) -> bool {
+        self.multi_goal
+    }
+
+    fn set_multi_goal(&mut self, multi_goal: bool) {
+        self.multi_goal = multi_goal;
+
+        if !multi_goal && self.goals.len() > 1 {
+            for goal in self.goals.split_off(1) {
+                self.unset_end(goal, CellKind::Goal);
+            }
+        }
     }
 }
 
@@ -536,4 +611,22 @@ mod test_grid {
         assert!(!(grid.toggle_cell(100, 300, CellKind::Wall) == CellKind::Wall));
         assert!(grid.is_set(100, 300));
     }
+
+    #[test]
+    fn solving_without_ends_is_an_error() {
+        let mut grid = BlockGrid::with_dims(5, 5);
+        assert_eq!(grid.solve_path(), Err(MazeError::NoStart));
+        assert_eq!(
+            grid.step_solve_path(&mut Vec::new()),
+            Err(MazeError::NoStart)
+        );
+
+        grid.toggle_cell(1, 1, CellKind::Start);
+        grid.set_solver_kind(SolverKind::AStar);
+        assert_eq!(grid.reset_solver(), Ok(()));
+        assert_eq!(grid.solve_path(), Err(MazeError::NoGoal));
+
+        grid.toggle_cell(3, 3, CellKind::Goal);
+        assert_eq!(grid.solve_path(), Ok(()));
+    }
 }
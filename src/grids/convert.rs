@@ -0,0 +1,175 @@
+// lossless conversion between a wall grid and the (2n+1)x(2m+1) block grid drawing the same maze.
+// maze cell (row, column) sits at block (2 * row + 1, 2 * column + 1), the blocks between two
+// of those are the walls and every block with two even coordinates is a wall corner
+use crate::error::{MazeError, MazeResult};
+use crate::grids::block_grid::BlockGrid;
+use crate::grids::wall_grid::WallGrid;
+use crate::grids::{CellKind, Grid, GridKind};
+
+pub fn block_cell_of(cell: (usize, usize)) -> (usize, usize) {
+    ((2 * cell.0) + 1, (2 * cell.1) + 1)
+}
+
+// None for blocks that are walls or corners rather than maze cells
+pub fn maze_cell_of(block: (usize, usize)) -> Option<(usize, usize)> {
+    if block.0 % 2 == 1 && block.1 % 2 == 1 {
+        Some((block.0 / 2, block.1 / 2))
+    } else {
+        None
+    }
+}
+
+// start, goals and solver settings, placed through the grid so it keeps track of them
+fn copy_ends(from: &dyn Grid, to: &mut dyn Grid, map: impl Fn((usize, usize)) -> (usize, usize)) {
+    to.set_solver_kind(from.solver_kind());
+    to.set_multi_goal(from.multi_goal());
+    to.set_start(from.start().map(&map));
+    for goal in from.goals() {
+        let (row, column) = map(goal);
+        to.toggle_cell(row, column, CellKind::Goal);
+    }
+}
+
+fn without_ends(kind: CellKind) -> CellKind {
+    match kind {
+        CellKind::Start | CellKind::Goal => CellKind::Empty,
+        kind => kind,
+    }
+}
+
+pub fn wall_to_block(grid: &dyn Grid) -> BlockGrid {
+    let dims = grid.dims();
+    let mut block = BlockGrid::with_dims((2 * dims.rows) + 1, (2 * dims.columns) + 1);
+    block.fill();
+
+    for row in 0..dims.rows {
+        for column in 0..dims.columns {
+            let (block_row, block_column) = block_cell_of((row, column));
+            block.set_cell(
+                block_row,
+                block_column,
+                without_ends(grid.get_cell(row, column)),
+            );
+
+            if column + 1 < dims.columns && grid.is_passable((row, column), (row, column + 1)) {
+                block.set_cell(block_row, block_column + 1, CellKind::Empty);
+            }
+            if row + 1 < dims.rows && grid.is_passable((row, column), (row + 1, column)) {
+                block.set_cell(block_row + 1, block_column, CellKind::Empty);
+            }
+        }
+    }
+
+    copy_ends(grid, &mut block, block_cell_of);
+    block
+}
+
+pub fn block_to_wall(grid: &dyn Grid) -> MazeResult<WallGrid> {
+    let dims = grid.dims();
+    if dims.rows % 2 == 0 || dims.columns % 2 == 0 {
+        return Err(MazeError::Unsupported(
+            "converting a block grid with even dimensions",
+        ));
+    }
+    let ends = grid.start().into_iter().chain(grid.goals());
+    let ends = ends.map(maze_cell_of).collect::<Option<Vec<_>>>();
+    if ends.is_none() {
+        return Err(MazeError::Unsupported(
+            "converting a start or goal placed on a wall",
+        ));
+    }
+
+    let (rows, columns) = (dims.rows / 2, dims.columns / 2);
+    let mut wall = WallGrid::with_dims(rows, columns);
+    for row in 0..rows {
+        for column in 0..columns {
+            let (block_row, block_column) = block_cell_of((row, column));
+            wall.set_cell(
+                row,
+                column,
+                without_ends(grid.get_cell(block_row, block_column)),
+            );
+
+            if column + 1 < columns && grid.get_cell(block_row, block_column + 1) != CellKind::Wall
+            {
+                wall.clear_wall_between((row, column), (row, column + 1))?;
+            }
+            if row + 1 < rows && grid.get_cell(block_row + 1, block_column) != CellKind::Wall {
+                wall.clear_wall_between((row, column), (row + 1, column))?;
+            }
+        }
+    }
+
+    copy_ends(grid, &mut wall, |block| (block.0 / 2, block.1 / 2));
+    Ok(wall)
+}
+
+pub fn convert_grid(grid: &dyn Grid, from: GridKind, to: GridKind) -> MazeResult<Box<dyn Grid>> {
+    match (from, to) {
+        (GridKind::Wall, GridKind::Block) => Ok(Box::new(wall_to_block(grid))),
+        (GridKind::Block, GridKind::Wall) => Ok(Box::new(block_to_wall(grid)?)),
+        _ => Err(MazeError::Unsupported("converting a grid to its own kind")),
+    }
+}
+
+#[cfg(test)]
+mod test_convert {
+    use super::*;
+    use crate::generators::generate_grid;
+    use crate::generators::GeneratorKind;
+
+    fn sorted(mut paths: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn round_trips_a_generated_maze() {
+        let mut wall = generate_grid(GeneratorKind::AldousBroder, 6, 9, GridKind::Wall, 3).unwrap();
+        wall.set_multi_goal(true);
+        wall.toggle_cell(0, 0, CellKind::Start);
+        wall.toggle_cell(5, 8, CellKind::Goal);
+        wall.toggle_cell(2, 4, CellKind::Goal);
+
+        let block = wall_to_block(wall.as_ref());
+        assert_eq!((block.dims.rows, block.dims.columns), (13, 19));
+        assert_eq!(block.start, Some((1, 1)));
+        assert_eq!(block.goals, vec![(11, 17), (5, 9)]);
+        assert_eq!(
+            sorted(block.paths().unwrap()),
+            sorted(wall.paths().unwrap())
+        );
+        // a spanning tree on 54 cells leaves 53 of the walls between them open
+        let open_walls = (0..13)
+            .flat_map(|row| (0..19).map(move |column| (row, column)))
+            .filter(|(row, column)| (row + column) % 2 == 1)
+            .filter(|(row, column)| block.get_cell(*row, *column) != CellKind::Wall)
+            .count();
+        assert_eq!(open_walls, 53);
+
+        let back = block_to_wall(&block).unwrap();
+        assert_eq!(back.cells, *wall.cells());
+        assert_eq!(back.start, wall.start());
+        assert_eq!(back.goals, wall.goals());
+        assert_eq!(sorted(back.paths().unwrap()), sorted(wall.paths().unwrap()));
+    }
+
+    #[test]
+    fn block_walls_sit_between_odd_cells() {
+        let mut block = BlockGrid::with_dims(5, 5);
+        block.fill();
+        block.clear_wall_between((1, 1), (1, 3)).unwrap();
+        assert_eq!(block.get_cell(1, 2), CellKind::Empty);
+        assert_eq!(block.paths().unwrap(), vec![(0, 1), (1, 0)]);
+
+        assert_eq!(
+            block.add_wall_between((1, 1), (2, 1)),
+            Err(MazeError::NotAdjacent((1, 1), (2, 1)))
+        );
+
+        block.set_paths(vec![(0, 2), (2, 0)]).unwrap();
+        assert_eq!(block.get_cell(1, 2), CellKind::Wall);
+        assert_eq!(block.get_cell(2, 1), CellKind::Empty);
+        assert!(block_to_wall(&BlockGrid::with_dims(4, 5)).is_err());
+    }
+}
@@ -1,16 +1,21 @@
 #[allow(dead_code)]
 pub mod block_grid;
 
+pub mod convert;
+
 #[allow(dead_code)]
 pub mod wall_grid;
 
-#[derive(Debug, Clone, Copy, PartialOrd, PartialEq)]
+use crate::error::MazeResult;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Serialize, Deserialize)]
 pub enum GridKind {
     Block,
     Wall,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Dimensions {
     pub rows: usize,
     pub columns: usize,
@@ -120,7 +125,7 @@ impl Iterator for Neighborhood {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 // @TODO @FIXME should be renamed to CellKind
 pub enum CellKind {
     Empty = 0,
@@ -161,31 +166,47 @@ impl From<CellKind> for [f32; 4] {
     }
 }
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum SolverKind {
     DFS,
     BFS,
     AStar,
 }
 
+// largest rows or columns a grid gets from a typed size or a file, the sliders stop at 255
+pub const MAX_DIMENSION: usize = 4096;
+
+pub fn new_grid(grid_kind: GridKind, rows: usize, columns: usize) -> Box<dyn Grid> {
+    match grid_kind {
+        GridKind::Block => Box::new(block_grid::BlockGrid::with_dims(rows, columns)),
+        GridKind::Wall => Box::new(wall_grid::WallGrid::with_dims(rows, columns)),
+    }
+}
+
+// cells a solver has drawn on, which starting it over wipes
+fn solver_marks(cells: &[CellKind]) -> impl Iterator<Item = usize> + '_ {
+    cells
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| {
+            **cell == CellKind::Path || **cell == CellKind::Explored || **cell == CellKind::Cursor
+        })
+        .map(|(index, _)| index)
+}
+
 pub trait Grid {
-    fn render(&self, state: &crate::State) -> Vec<crate::renderer::Vertex>;
     fn dims(&self) -> Dimensions;
     fn cells(&self) -> &Vec<CellKind>;
     fn set_cells(&mut self, cells: Vec<CellKind>);
     fn solver_kind(&self) -> SolverKind;
     fn set_solver_kind(&mut self, kind: SolverKind);
-    fn solve_path(&mut self);
-    fn step_solve_path(&mut self) -> bool;
-    fn reset_solver(&mut self);
+    fn solve_path(&mut self) -> MazeResult<()>;
+    // Ok(false) once the walk back from the goal reaches the start or nothing is left to explore.
+    // the indices of every cell the step rewrote are pushed onto changed
+    fn step_solve_path(&mut self, changed: &mut Vec<usize>) -> MazeResult<bool>;
+    fn reset_solver(&mut self) -> MazeResult<()>;
     fn clear(&mut self);
     fn fill(&mut self);
-    fn handle_click(
-        &mut self,
-        pos: (f32, f32),
-        size: winit::dpi::PhysicalSize<u32>,
-        kind: CellKind,
-    );
     fn get_neighborhood_of(&self, row: usize, column: usize) -> Neighborhood;
     fn set_neighbor_of(
         &mut self,
@@ -194,9 +215,20 @@ pub trait Grid {
         kind: CellKind,
     ) -> (usize, usize);
     fn set_cell(&mut self, row: usize, column: usize, kind: CellKind) -> CellKind;
+    // places kind, or clears the cell if it already holds it, keeping start and goals in step
+    fn toggle_cell(&mut self, row: usize, column: usize, kind: CellKind) -> CellKind;
     fn get_cell(&self, row: usize, column: usize) -> CellKind;
-    fn paths(&self) -> Vec<(usize, usize)>;
-    fn set_paths(&mut self, paths: Vec<(usize, usize)>);
-    fn add_wall_between(&mut self, one: (usize, usize), two: (usize, usize));
-    fn clear_wall_between(&mut self, one: (usize, usize), two: (usize, usize));
+    fn paths(&self) -> MazeResult<Vec<(usize, usize)>>;
+    fn set_paths(&mut self, paths: Vec<(usize, usize)>) -> MazeResult<()>;
+    fn add_wall_between(&mut self, one: (usize, usize), two: (usize, usize)) -> MazeResult<()>;
+    fn clear_wall_between(&mut self, one: (usize, usize), two: (usize, usize)) -> MazeResult<()>;
+    // whether a step between two adjacent cells is allowed
+    fn is_passable(&self, one: (usize, usize), two: (usize, usize)) -> bool;
+    fn start(&self) -> Option<(usize, usize)>;
+    fn set_start(&mut self, start: Option<(usize, usize)>);
+    fn goals(&self) -> Vec<(usize, usize)>;
+    // replaces every goal with this one
+    fn set_goal(&mut self, goal: Option<(usize, usize)>);
+    fn multi_goal(&self) -> bool;
+    fn set_multi_goal(&mut self, multi_goal: bool);
 }
@@ -1,16 +1,12 @@
 const DEFAULT_DIMS: (usize, usize) = (15, 15);
 
+use crate::error::{MazeError, MazeResult};
 use crate::grids::{CellKind, Dimensions, Direction, Grid, Neighborhood, SolverKind};
 use bit_graph::search::a_star::AStarMH;
 use bit_graph::search::bfs::BFS;
 use bit_graph::search::dfs::DFS;
 use bit_graph::search::Pathfinder;
 use bit_graph::{BitGraph, Graph};
-pub const GRID_SCALE: f32 = 1.3;
-pub const SQUARE_GAP: f32 = 0.005;
-
-use crate::renderer::Vertex;
-use crate::State;
 
 type WalledCell = u8;
 
@@ -23,6 +19,9 @@ pub struct WallGrid {
 
     pub start: Option<(usize, usize)>,
     pub goal: Option<(usize, usize)>,
+    // every placed goal, `goal` is always the first of these
+    pub goals: Vec<(usize, usize)>,
+    pub multi_goal: bool,
     pub cursor: Option<(usize, usize)>,
     pub solver: Option<Box<dyn Pathfinder<u64, bool>>>,
     pub solver_kind: SolverKind,
@@ -42,6 +41,8 @@ impl WallGrid {
             graph,
             start: None,
             goal: None,
+            goals: Vec::new(),
+            multi_goal: false,
             cursor: None,
             solver: None,
             solver_kind: SolverKind::BFS,
@@ -68,39 +69,6 @@ impl WallGrid {
         (n_row, n_col)
     }
 
-    pub fn toggle_cell(&mut self, row: usize, column: usize, kind: CellKind) -> CellKind {
-        let index = self.index_of(row, column);
-        let prev_kind = self.cells[index];
-
-        if prev_kind == CellKind::Empty {
-            self.cells[index] = kind;
-        } else if prev_kind != kind {
-            self.cells[index] = kind;
-        } else {
-            self.cells[index] = CellKind::Empty;
-        }
-
-        if kind == CellKind::Start {
-            if let Some(start) = self.start {
-                self.unset_cell(start.0, start.1);
-            }
-            if self.cells[index] == CellKind::Start {
-                self.start = Some((row, column));
-            }
-        }
-
-        if kind == CellKind::Goal {
-            if let Some(goal) = self.goal {
-                self.unset_cell(goal.0, goal.1);
-            }
-            if self.cells[index] == CellKind::Goal {
-                self.goal = Some((row, column));
-            }
-        }
-
-        prev_kind
-    }
-
     #[inline]
     fn index_of(&self, row: usize, column: usize) -> usize {
         (self.dims.columns * row) + column
@@ -111,27 +79,29 @@ impl WallGrid {
         self.set_cell(row, column, CellKind::Empty)
     }
 
-    fn get_ndc_params(&self, size: winit::dpi::PhysicalSize<u32>) -> (f32, f32, f32, f32) {
-        let ratio = size.width as f32 / size.height as f32;
-        let (sq_width, sq_height) = if ratio >= 1.0 {
-            (
-                GRID_SCALE / self.dims.columns as f32 / ratio,
-                GRID_SCALE / self.dims.rows as f32,
-            )
-        } else {
-            (
-                GRID_SCALE / self.dims.columns as f32,
-                GRID_SCALE / self.dims.rows as f32 * ratio,
-            )
-        };
+    // clears an old start or goal, unless something else has been placed over it since
+    fn unset_end(&mut self, (row, column): (usize, usize), kind: CellKind) {
+        if self.get_cell(row, column) == kind {
+            self.unset_cell(row, column);
+        }
+    }
 
-        // centers the grid somehow, this will need some additional calculations to detect large
-        // gaps, but good enough for now to buy space
-        let bottom_left_x =
-            (2.0 - (GRID_SCALE + (self.dims.columns as f32 * SQUARE_GAP))) / 2.0 - 1.0;
-        let bottom_left_y = (2.0 - (GRID_SCALE + (self.dims.rows as f32 * SQUARE_GAP))) / 2.0 - 1.0;
+    // graph indices of two cells that share a wall
+    fn neighbor_indices(
+        &self,
+        one: (usize, usize),
+        two: (usize, usize),
+    ) -> MazeResult<(usize, usize)> {
+        for cell in &[one, two] {
+            if cell.0 >= self.dims.rows || cell.1 >= self.dims.columns {
+                return Err(MazeError::OutOfBounds(*cell));
+            }
+        }
+        if one.0.max(two.0) - one.0.min(two.0) + one.1.max(two.1) - one.1.min(two.1) != 1 {
+            return Err(MazeError::NotAdjacent(one, two));
+        }
 
-        (sq_width, sq_height, bottom_left_x, bottom_left_y)
+        Ok((self.index_of(one.0, one.1), self.index_of(two.0, two.1)))
     }
 }
 
@@ -149,198 +119,61 @@ impl Grid for WallGrid {
         prev_kind
     }
 
-    fn clear_wall_between(&mut self, one: (usize, usize), two: (usize, usize)) {
-        let index_one = self.index_of(one.0, one.1);
-        let index_two = self.index_of(two.0, two.1);
+    fn toggle_cell(&mut self, row: usize, column: usize, kind: CellKind) -> CellKind {
+        let index = self.index_of(row, column);
+        let prev_kind = self.cells[index];
+
+        if prev_kind == CellKind::Empty {
+            self.cells[index] = kind;
+        } else if prev_kind != kind {
+            self.cells[index] = kind;
+        } else {
+            self.cells[index] = CellKind::Empty;
+        }
+
+        if kind == CellKind::Start {
+            if let Some(start) = self.start {
+                self.unset_cell(start.0, start.1);
+            }
+            if self.cells[index] == CellKind::Start {
+                self.start = Some((row, column));
+            }
+        }
+
+        if kind == CellKind::Goal || prev_kind == CellKind::Goal {
+            if kind == CellKind::Goal && !self.multi_goal {
+                for goal in std::mem::take(&mut self.goals) {
+                    if goal != (row, column) {
+                        self.unset_cell(goal.0, goal.1);
+                    }
+                }
+            }
+            self.goals.retain(|goal| *goal != (row, column));
+            if self.cells[index] == CellKind::Goal {
+                self.goals.push((row, column));
+            }
+            self.goal = self.goals.first().cloned();
+        }
+
+        prev_kind
+    }
+
+    fn clear_wall_between(&mut self, one: (usize, usize), two: (usize, usize)) -> MazeResult<()> {
+        let (index_one, index_two) = self.neighbor_indices(one, two)?;
 
         // graph is directed and im lazy to make undirected
         self.graph.add_edge(index_one, index_two);
         self.graph.add_edge(index_two, index_one);
+        Ok(())
     }
 
-    fn add_wall_between(&mut self, one: (usize, usize), two: (usize, usize)) {
-        let index_one = self.index_of(one.0, one.1);
-        let index_two = self.index_of(two.0, two.1);
+    fn add_wall_between(&mut self, one: (usize, usize), two: (usize, usize)) -> MazeResult<()> {
+        let (index_one, index_two) = self.neighbor_indices(one, two)?;
 
         // graph is directed and im lazy to make undirected
         self.graph.remove_edge(index_one, index_two);
         self.graph.remove_edge(index_two, index_one);
-    }
-
-    fn handle_click(
-        &mut self,
-        pos: (f32, f32),
-        size: winit::dpi::PhysicalSize<u32>,
-        kind: CellKind,
-    ) {
-        let x = (2.0 * pos.0) - 1.0;
-        let y = (2.0 * pos.1) - 1.0;
-        let y = -y;
-
-        let (sq_width, sq_height, bottom_left_x, bottom_left_y) = self.get_ndc_params(size);
-
-        let (row, column) = {
-            let x = x - bottom_left_x;
-            let y = y - bottom_left_y;
-
-            if x < 0. || y < 0. {
-                return;
-            }
-
-            (
-                (y / (sq_height + SQUARE_GAP)) as usize,
-                (x / (sq_width + SQUARE_GAP)) as usize,
-            )
-        };
-
-        if row < self.dims.rows && column < self.dims.columns {
-            self.toggle_cell(row, column, kind);
-        }
-    }
-
-    fn render(&self, state: &State) -> Vec<Vertex> {
-        let mut grid = Vec::new();
-        let (sq_width, sq_height, center_x, center_y) = self.get_ndc_params(state.gfx_ctx.size);
-
-        let mut offset_x = center_x;
-        let mut offset_y = center_y;
-
-        //@TODO factor in GRID_SCALE somehow so that the grid has a margin from the border of the
-        //screen, make boxes touch?
-        for row in 0..self.dims.rows {
-            for col in 0..self.dims.columns {
-                let low_x = offset_x;
-                let low_y = offset_y;
-
-                let up_x = low_x + sq_width;
-                let up_y = low_y + sq_height;
-
-                let color: [f32; 4] = self.get_cell(row, col).into();
-
-                let verts: &[Vertex] = &[
-                    // lower left triangle
-                    Vertex {
-                        position: [low_x, low_y],
-                        color,
-                    },
-                    Vertex {
-                        position: [up_x, low_y],
-                        color,
-                    },
-                    Vertex {
-                        position: [low_x, up_y],
-                        color,
-                    },
-                    // upper right triangle
-                    Vertex {
-                        position: [low_x, up_y],
-                        color,
-                    },
-                    Vertex {
-                        position: [up_x, low_y],
-                        color,
-                    },
-                    Vertex {
-                        position: [up_x, up_y],
-                        color,
-                    },
-                ];
-
-                grid.append(&mut Vec::from(verts));
-
-                offset_x += sq_width + SQUARE_GAP;
-                if col != self.dims.columns - 1 {
-                    let index = (row * self.dims.columns) + col;
-                    let color: [f32; 4] = if self.graph.has_edge(index, index + 1) {
-                        color
-                    } else {
-                        [0.0, 0.0, 0.0, 1.0]
-                    };
-                    let low_x = low_x + sq_width;
-                    let up_x = low_x + SQUARE_GAP;
-                    //let up_y = up_y + SQUARE_GAP;
-
-                    let verts: &[Vertex] = &[
-                        // lower left triangle
-                        Vertex {
-                            position: [low_x, low_y],
-                            color,
-                        },
-                        Vertex {
-                            position: [up_x, low_y],
-                            color,
-                        },
-                        Vertex {
-                            position: [low_x, up_y],
-                            color,
-                        },
-                        // upper right triangle
-                        Vertex {
-                            position: [low_x, up_y],
-                            color,
-                        },
-                        Vertex {
-                            position: [up_x, low_y],
-                            color,
-                        },
-                        Vertex {
-                            position: [up_x, up_y],
-                            color,
-                        },
-                    ];
-
-                    grid.append(&mut Vec::from(verts));
-                }
-
-                if row != self.dims.rows - 1 {
-                    let index = (row * self.dims.columns) + col;
-
-                    let color: [f32; 4] = if self.graph.has_edge(index, index + self.dims.columns) {
-                        color
-                    } else {
-                        [0.0, 0.0, 0.0, 1.0]
-                    };
-                    let up_x = low_x + sq_width; // + SQUARE_GAP
-                    let low_y = up_y;
-                    let up_y = low_y + SQUARE_GAP;
-
-                    let verts: &[Vertex] = &[
-                        // lower left triangle
-                        Vertex {
-                            position: [low_x, low_y],
-                            color,
-                        },
-                        Vertex {
-                            position: [up_x, low_y],
-                            color,
-                        },
-                        Vertex {
-                            position: [low_x, up_y],
-                            color,
-                        },
-                        // upper right triangle
-                        Vertex {
-                            position: [low_x, up_y],
-                            color,
-                        },
-                        Vertex {
-                            position: [up_x, low_y],
-                            color,
-                        },
-                        Vertex {
-                            position: [up_x, up_y],
-                            color,
-                        },
-                    ];
-
-                    grid.append(&mut Vec::from(verts));
-                }
-            }
-            offset_y += sq_height + SQUARE_GAP;
-            offset_x = center_x;
-        }
-
-        grid
+        Ok(())
     }
 
     fn cells(&self) -> &Vec<CellKind> {
@@ -353,21 +186,26 @@ impl Grid for WallGrid {
 
     fn set_solver_kind(&mut self, kind: super::SolverKind) {
         self.solver_kind = kind;
-        self.reset_solver();
+        // rebuilt on the next solve, which is also where a missing start gets reported
+        self.solver = None;
     }
 
-    fn solve_path(&mut self) {
-        let start = self.start.unwrap();
-        let goal = self.goal.unwrap();
+    fn solve_path(&mut self) -> MazeResult<()> {
+        let start = self.start.ok_or(MazeError::NoStart)?;
+        let goal = self.goal.ok_or(MazeError::NoGoal)?;
+        self.reset_solver()?;
         let root_idx = (start.0 * self.dims.columns) + start.1;
         let goal_idx = (goal.0 * self.dims.columns) + goal.1;
-        println!("start: {}, goal: {}", root_idx, goal_idx);
+        log::debug!("start: {}, goal: {}", root_idx, goal_idx);
         let graph = &*self.graph;
-        let mut solver = self.solver.take().unwrap();
+        let mut solver = match self.solver.take() {
+            Some(solver) => solver,
+            None => return Ok(()),
+        };
 
         if let Some(path) = solver.path_to(graph, goal_idx) {
             // pop off root
-            println!("Path found: {:?}", path);
+            log::debug!("Path found: {:?}", path);
 
             for i in 1..path.len() - 1 {
                 let row = path[i] / self.dims.columns;
@@ -376,49 +214,52 @@ impl Grid for WallGrid {
                 self.set_cell(row, col, CellKind::Path);
             }
         } else {
-            println!("path not found");
+            log::debug!("path not found");
         }
-    }
 
-    fn step_solve_path(&mut self) -> bool {
-        if self.start.is_none() || self.goal.is_none() {
-            return false;
-        }
+        Ok(())
+    }
 
-        let start = self.start.unwrap();
-        let goal = self.goal.unwrap();
+    fn step_solve_path(&mut self, changed: &mut Vec<usize>) -> MazeResult<bool> {
+        let start = self.start.ok_or(MazeError::NoStart)?;
+        let goal = self.goal.ok_or(MazeError::NoGoal)?;
         self.set_cell(start.0, start.1, CellKind::Start);
         self.set_cell(goal.0, goal.1, CellKind::Goal);
+        changed.push(self.index_of(start.0, start.1));
+        changed.push(self.index_of(goal.0, goal.1));
 
-        let mut index = 0;
-        loop {
+        // the cell explored last step is only the cursor until this one
+        if let Some((row, col)) = self.cursor {
+            let index = (row * self.dims.columns) + col;
             if self.cells[index] == CellKind::Cursor {
                 self.cells[index] = CellKind::Explored;
-                break;
-            }
-            index += 1;
-
-            if index >= self.cells.len() {
-                break;
+                changed.push(index);
             }
         }
 
         if self.solver.is_none() {
-            self.reset_solver();
+            changed.extend(super::solver_marks(&self.cells));
+            self.reset_solver()?;
         }
 
-        let solver = self.solver.as_mut().unwrap();
         let graph = &*self.graph;
+        let solver = match self.solver.as_mut() {
+            Some(solver) => solver,
+            None => return Ok(false),
+        };
 
         let (row, col, kind) = if solver.is_solved() {
-            let cursor = self.cursor.unwrap();
+            let cursor = match self.cursor {
+                Some(cursor) => cursor,
+                None => return Ok(false),
+            };
             let idx = (cursor.0 * self.dims.columns) + cursor.1;
             let from = solver.from_index_of(idx);
             let row = from / self.dims.columns;
             let col = from % self.dims.columns;
 
             if row == start.0 && col == start.1 {
-                return false;
+                return Ok(false);
             }
 
             self.cursor = Some((row, col));
@@ -431,12 +272,12 @@ impl Grid for WallGrid {
 
                 if row == goal.0 && col == goal.1 {
                     solver.set_solved();
-                    self.cursor = Some((row, col));
                 }
+                self.cursor = Some((row, col));
 
                 (row, col, CellKind::Cursor)
             } else {
-                return false;
+                return Ok(false);
             };
 
             (row, col, kind)
@@ -444,13 +285,15 @@ impl Grid for WallGrid {
         drop(solver);
 
         self.set_cell(row, col, kind);
-        true
+        changed.push((row * self.dims.columns) + col);
+        Ok(true)
     }
 
     fn clear(&mut self) {
         self.cells = vec![CellKind::Empty; self.cells.len()];
         self.start = None;
         self.goal = None;
+        self.goals.clear();
         self.cursor = None;
     }
 
@@ -513,24 +356,21 @@ impl Grid for WallGrid {
         self.solver_kind
     }
 
-    fn reset_solver(&mut self) {
+    fn reset_solver(&mut self) -> MazeResult<()> {
         self.cells.iter_mut().for_each(|cell| {
             if *cell == CellKind::Explored || *cell == CellKind::Cursor {
                 *cell = CellKind::Empty;
             }
         });
+        self.cursor = None;
         let graph = &*self.graph;
-        let root = self.start.unwrap();
+        let root = self.start.ok_or(MazeError::NoStart)?;
         let index = (self.dims.columns * root.0) + root.1;
         let goal = match self.goal {
             Some(inner) => inner,
-            None => {
-                if self.solver_kind == SolverKind::AStar {
-                    panic!("Astar requires a goal")
-                } else {
-                    (0, 0)
-                }
-            }
+            // astar needs somewhere to aim, the others only use the goal to stop
+            None if self.solver_kind == SolverKind::AStar => return Err(MazeError::NoGoal),
+            None => (0, 0),
         };
         let goal_idx = (self.dims.columns * goal.0) + goal.1;
         self.solver = Some(match self.solver_kind {
@@ -538,17 +378,151 @@ impl Grid for WallGrid {
             SolverKind::DFS => Box::new(DFS::new(graph, index)),
             SolverKind::AStar => Box::new(AStarMH::new(graph, index, goal_idx, self.dims.columns)),
         });
+        Ok(())
     }
 
-    fn paths(&self) -> Vec<(usize, usize)> {
-        self.graph.all_edge_pairs()
+    fn paths(&self) -> MazeResult<Vec<(usize, usize)>> {
+        Ok(self.graph.all_edge_pairs())
     }
 
-    fn set_paths(&mut self, paths: Vec<(usize, usize)>) {
+    fn set_paths(&mut self, paths: Vec<(usize, usize)>) -> MazeResult<()> {
+        let count = self.dims.rows * self.dims.columns;
+        if let Some(index) = paths
+            .iter()
+            .map(|(from, to)| *from.max(to))
+            .find(|i| *i >= count)
+        {
+            return Err(MazeError::OutOfBounds((
+                index / self.dims.columns,
+                index % self.dims.columns,
+            )));
+        }
+
         self.fill();
 
         paths.iter().for_each(|(from, to)| {
             self.graph.add_edge(*from, *to);
         });
+        Ok(())
+    }
+
+    fn is_passable(&self, one: (usize, usize), two: (usize, usize)) -> bool {
+        self.graph
+            .has_edge(self.index_of(one.0, one.1), self.index_of(two.0, two.1))
+    }
+
+    fn start(&self) -> Option<(usize, usize)> {
+        self.start
+    }
+
+    fn set_start(&mut self, start: Option<(usize, usize)>) {
+        if let Some(old) = self.start.take() {
+            self.unset_end(old, CellKind::Start);
+        }
+        if let Some(start) = start {
+            self.set_cell(start.0, start.1, CellKind::Start);
+        }
+        self.start = start;
+    }
+
+    fn goals(&self) -> Vec<(usize, usize)> {
+        self.goals.clone()
+    }
+
+    fn set_goal(&mut self, goal: Option<(usize, usize)>) {
+        for old in std::mem::take(&mut self.goals) {
+            self.unset_end(old, CellKind::Goal);
+        }
+        if let Some(goal) = goal {
+            self.set_cell(goal.0, goal.1, CellKind::Goal);
+            self.goals.push(goal);
+        }
+        self.goal = goal;
+    }
+
+    fn multi_goal(&self) -> bool {
+        self.multi_goal
+    }
+
+    fn set_multi_goal(&mut self, multi_goal: bool) {
+        self.multi_goal = multi_goal;
+
+        if !multi_goal && self.goals.len() > 1 {
+            for goal in self.goals.split_off(1) {
+                self.unset_end(goal, CellKind::Goal);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_grid {
+    use super::*;
+
+    #[test]
+    fn solving_without_ends_is_an_error() {
+        let mut grid = WallGrid::with_dims(3, 3);
+        grid.set_solver_kind(SolverKind::AStar);
+        assert_eq!(grid.reset_solver(), Err(MazeError::NoStart));
+
+        grid.toggle_cell(0, 0, CellKind::Start);
+        assert_eq!(grid.reset_solver(), Err(MazeError::NoGoal));
+        assert_eq!(
+            grid.step_solve_path(&mut Vec::new()),
+            Err(MazeError::NoGoal)
+        );
+
+        grid.toggle_cell(0, 1, CellKind::Goal);
+        grid.clear_wall_between((0, 0), (0, 1)).unwrap();
+        let mut changed = Vec::new();
+        assert_eq!(grid.step_solve_path(&mut changed), Ok(true));
+        // the ends, then the start as the first cell explored
+        assert_eq!(changed, vec![0, 1, 0]);
+    }
+
+    #[test]
+    fn steps_move_one_cursor_and_report_it() {
+        let mut grid = WallGrid::with_dims(1, 5);
+        for column in 0..4 {
+            grid.clear_wall_between((0, column), (0, column + 1))
+                .unwrap();
+        }
+        grid.set_start(Some((0, 0)));
+        grid.set_goal(Some((0, 4)));
+        grid.reset_solver().unwrap();
+
+        loop {
+            let before = grid.cells.clone();
+            let mut changed = Vec::new();
+            let stepped = grid.step_solve_path(&mut changed).unwrap();
+            for (index, (old, new)) in before.iter().zip(&grid.cells).enumerate() {
+                if old != new {
+                    assert!(changed.contains(&index), "{} not reported", index);
+                }
+            }
+            let cursors = grid.cells.iter().filter(|cell| **cell == CellKind::Cursor);
+            assert!(cursors.count() <= 1);
+            if !stepped {
+                break;
+            }
+        }
+        assert_eq!(
+            grid.cells[1..4],
+            [CellKind::Path, CellKind::Path, CellKind::Path]
+        );
+    }
+
+    #[test]
+    fn walls_only_sit_between_neighbours() {
+        let mut grid = WallGrid::with_dims(3, 3);
+        assert_eq!(
+            grid.clear_wall_between((0, 0), (1, 1)),
+            Err(MazeError::NotAdjacent((0, 0), (1, 1)))
+        );
+        assert_eq!(
+            grid.add_wall_between((2, 2), (2, 3)),
+            Err(MazeError::OutOfBounds((2, 3)))
+        );
+        assert!(grid.paths().unwrap().is_empty());
     }
 }
@@ -0,0 +1,441 @@
+// undo and redo for grid edits. each entry only keeps the cells and walls the edit changed, the
+// full copy taken beforehand is dropped as soon as the diff is made. small edits skip the full
+// copy and hand over each cell and wall before they change it
+use crate::error::MazeResult;
+use crate::grids::{CellKind, Grid, GridKind};
+use std::collections::HashMap;
+
+const DEFAULT_LIMIT: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Ends {
+    start: Option<(usize, usize)>,
+    goals: Vec<(usize, usize)>,
+    multi_goal: bool,
+}
+
+impl Ends {
+    fn of(grid: &dyn Grid) -> Self {
+        Self {
+            start: grid.start(),
+            goals: grid.goals(),
+            multi_goal: grid.multi_goal(),
+        }
+    }
+}
+
+type Wall = ((usize, usize), (usize, usize));
+
+// the grid as it was before an edit
+pub struct Snapshot {
+    before: Before,
+    ends: Ends,
+}
+
+enum Before {
+    Whole {
+        cells: Vec<CellKind>,
+        // east and north passages of every cell, wall grids only since block grid walls are cells
+        passages: Vec<[bool; 2]>,
+    },
+    // by index and by wall, whether it was open
+    Touched {
+        cells: HashMap<usize, CellKind>,
+        walls: HashMap<Wall, bool>,
+    },
+}
+
+impl Snapshot {
+    // every cell and wall, for edits that can reach anywhere
+    pub fn of(grid: &dyn Grid, kind: GridKind) -> Self {
+        Self {
+            before: Before::Whole {
+                cells: grid.cells().clone(),
+                passages: passages_of(grid, kind),
+            },
+            ends: Ends::of(grid),
+        }
+    }
+
+    // nothing but the cells under the start and goals, the edit touches everything else first
+    pub fn touched(grid: &dyn Grid) -> Self {
+        let ends = Ends::of(grid);
+        let columns = grid.dims().columns;
+        let cells = ends
+            .start
+            .iter()
+            .chain(&ends.goals)
+            .map(|&(row, column)| ((row * columns) + column, grid.get_cell(row, column)))
+            .collect();
+
+        Self {
+            before: Before::Touched {
+                cells,
+                walls: HashMap::new(),
+            },
+            ends,
+        }
+    }
+
+    // kind is the cell before the edit changed it, only the first one handed over counts
+    pub fn touch_cell(&mut self, index: usize, kind: CellKind) {
+        if let Before::Touched { cells, .. } = &mut self.before {
+            cells.entry(index).or_insert(kind);
+        }
+    }
+
+    pub fn touch_wall(&mut self, one: (usize, usize), two: (usize, usize), open: bool) {
+        if let Before::Touched { walls, .. } = &mut self.before {
+            walls.entry((one.min(two), one.max(two))).or_insert(open);
+        }
+    }
+}
+
+fn passages_of(grid: &dyn Grid, kind: GridKind) -> Vec<[bool; 2]> {
+    if kind != GridKind::Wall {
+        return Vec::new();
+    }
+
+    let dims = grid.dims();
+    let mut passages = Vec::with_capacity(dims.rows * dims.columns);
+    for row in 0..dims.rows {
+        for column in 0..dims.columns {
+            passages.push([
+                column + 1 < dims.columns && grid.is_passable((row, column), (row, column + 1)),
+                row + 1 < dims.rows && grid.is_passable((row, column), (row + 1, column)),
+            ]);
+        }
+    }
+    passages
+}
+
+#[derive(Debug, Clone)]
+pub struct Edit {
+    pub label: String,
+    // (index, before, after)
+    cells: Vec<(usize, CellKind, CellKind)>,
+    // (one, two, open after)
+    walls: Vec<((usize, usize), (usize, usize), bool)>,
+    ends: (Ends, Ends),
+}
+
+impl Edit {
+    fn between(label: &str, before: Snapshot, grid: &dyn Grid, kind: GridKind) -> Self {
+        let (cells, walls) = match before.before {
+            Before::Whole { cells, passages } => whole_diff(&cells, &passages, grid, kind),
+            Before::Touched { cells, walls } => touched_diff(cells, walls, grid),
+        };
+
+        Self {
+            label: label.to_string(),
+            cells,
+            walls,
+            ends: (before.ends, Ends::of(grid)),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.cells.is_empty() && self.walls.is_empty() && self.ends.0 == self.ends.1
+    }
+
+    fn apply(&self, grid: &mut dyn Grid, forward: bool) -> MazeResult<()> {
+        let columns = grid.dims().columns;
+        for (index, before, after) in &self.cells {
+            let kind = if forward { after } else { before };
+            grid.set_cell(index / columns, index % columns, *kind);
+        }
+
+        for (one, two, open) in &self.walls {
+            if *open == forward {
+                grid.clear_wall_between(*one, *two)?;
+            } else {
+                grid.add_wall_between(*one, *two)?;
+            }
+        }
+
+        let ends = if forward { &self.ends.1 } else { &self.ends.0 };
+        restore_ends(grid, ends);
+        // whatever the solver had walked is stale now
+        grid.set_solver_kind(grid.solver_kind());
+        Ok(())
+    }
+}
+
+type Diff = (
+    Vec<(usize, CellKind, CellKind)>,
+    Vec<((usize, usize), (usize, usize), bool)>,
+);
+
+fn whole_diff(cells: &[CellKind], passages: &[[bool; 2]], grid: &dyn Grid, kind: GridKind) -> Diff {
+    let changed = cells
+        .iter()
+        .zip(grid.cells())
+        .enumerate()
+        .filter(|(_, (before, after))| before != after)
+        .map(|(index, (before, after))| (index, *before, *after))
+        .collect();
+
+    let columns = grid.dims().columns;
+    let mut walls = Vec::new();
+    for (index, (before, after)) in passages.iter().zip(passages_of(grid, kind)).enumerate() {
+        let cell = (index / columns, index % columns);
+        if before[0] != after[0] {
+            walls.push((cell, (cell.0, cell.1 + 1), after[0]));
+        }
+        if before[1] != after[1] {
+            walls.push((cell, (cell.0 + 1, cell.1), after[1]));
+        }
+    }
+    (changed, walls)
+}
+
+fn touched_diff(
+    cells: HashMap<usize, CellKind>,
+    walls: HashMap<Wall, bool>,
+    grid: &dyn Grid,
+) -> Diff {
+    let mut changed: Vec<_> = cells
+        .into_iter()
+        .map(|(index, before)| (index, before, grid.cells()[index]))
+        .filter(|(_, before, after)| before != after)
+        .collect();
+    changed.sort_by_key(|(index, _, _)| *index);
+
+    let mut walls: Vec<_> = walls
+        .into_iter()
+        .map(|((one, two), before)| (one, two, before, grid.is_passable(one, two)))
+        .filter(|(_, _, before, after)| before != after)
+        .map(|(one, two, _, after)| (one, two, after))
+        .collect();
+    walls.sort();
+    (changed, walls)
+}
+
+// the cells are already right, but placing ends through the grid rewrites the cells under the
+// old and new ones, so those get put back afterwards
+fn restore_ends(grid: &mut dyn Grid, ends: &Ends) {
+    if Ends::of(grid) == *ends {
+        return;
+    }
+
+    let current = Ends::of(grid);
+    let touched: Vec<((usize, usize), CellKind)> = current
+        .start
+        .iter()
+        .chain(&current.goals)
+        .chain(&ends.start)
+        .chain(&ends.goals)
+        .map(|cell| (*cell, grid.get_cell(cell.0, cell.1)))
+        .collect();
+
+    grid.set_start(ends.start);
+    grid.set_multi_goal(ends.multi_goal);
+    grid.set_goal(None);
+    for goal in &ends.goals {
+        // toggling a cell that already reads Goal would clear it
+        grid.set_cell(goal.0, goal.1, CellKind::Empty);
+        grid.toggle_cell(goal.0, goal.1, CellKind::Goal);
+    }
+
+    for (cell, kind) in touched {
+        grid.set_cell(cell.0, cell.1, kind);
+    }
+}
+
+pub struct History {
+    done: Vec<Edit>,
+    undone: Vec<Edit>,
+    limit: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::with_limit(DEFAULT_LIMIT)
+    }
+}
+
+impl History {
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            done: Vec::new(),
+            undone: Vec::new(),
+            limit: limit.max(1),
+        }
+    }
+
+    // diffs grid against before, returns false when the edit changed nothing
+    pub fn commit(
+        &mut self,
+        label: &str,
+        before: Snapshot,
+        grid: &dyn Grid,
+        kind: GridKind,
+    ) -> bool {
+        let edit = Edit::between(label, before, grid, kind);
+        if edit.is_empty() {
+            return false;
+        }
+
+        self.undone.clear();
+        self.done.push(edit);
+        if self.done.len() > self.limit {
+            self.done.remove(0);
+        }
+        true
+    }
+
+    pub fn record<T>(
+        &mut self,
+        label: &str,
+        grid: &mut dyn Grid,
+        kind: GridKind,
+        edit: impl FnOnce(&mut dyn Grid) -> T,
+    ) -> T {
+        let before = Snapshot::of(grid, kind);
+        let value = edit(grid);
+        self.commit(label, before, grid, kind);
+        value
+    }
+
+    // label of the undone edit, None when there is nothing left
+    pub fn undo(&mut self, grid: &mut dyn Grid) -> MazeResult<Option<&str>> {
+        let edit = match self.done.pop() {
+            Some(edit) => edit,
+            None => return Ok(None),
+        };
+        edit.apply(grid, false)?;
+        self.undone.push(edit);
+        Ok(self.undone.last().map(|edit| edit.label.as_str()))
+    }
+
+    pub fn redo(&mut self, grid: &mut dyn Grid) -> MazeResult<Option<&str>> {
+        let edit = match self.undone.pop() {
+            Some(edit) => edit,
+            None => return Ok(None),
+        };
+        edit.apply(grid, true)?;
+        self.done.push(edit);
+        Ok(self.done.last().map(|edit| edit.label.as_str()))
+    }
+
+    // how many entries are applied, entries() past this point have been undone
+    pub fn position(&self) -> usize {
+        self.done.len()
+    }
+
+    // oldest first
+    pub fn entries(&self) -> impl Iterator<Item = &str> {
+        self.done
+            .iter()
+            .chain(self.undone.iter().rev())
+            .map(|edit| edit.label.as_str())
+    }
+
+    // undoes or redoes until position entries are applied
+    pub fn go_to(&mut self, grid: &mut dyn Grid, position: usize) -> MazeResult<()> {
+        while self.position() > position {
+            self.undo(grid)?;
+        }
+        while self.position() < position && !self.undone.is_empty() {
+            self.redo(grid)?;
+        }
+        Ok(())
+    }
+
+    // for when the grid is replaced and the diffs no longer line up with it
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+#[cfg(test)]
+mod test_history {
+    use super::*;
+    use crate::grids::wall_grid::WallGrid;
+
+    #[test]
+    fn undo_and_redo_walk_back_and_forth() {
+        let mut grid = WallGrid::with_dims(3, 3);
+        let mut history = History::default();
+
+        history.record("Start", &mut grid, GridKind::Wall, |grid| {
+            grid.toggle_cell(0, 0, CellKind::Start)
+        });
+        history.record("Goal", &mut grid, GridKind::Wall, |grid| {
+            grid.toggle_cell(2, 2, CellKind::Goal)
+        });
+        history.record("Carve", &mut grid, GridKind::Wall, |grid| {
+            grid.clear_wall_between((0, 0), (0, 1)).unwrap();
+            grid.toggle_cell(0, 0, CellKind::Start);
+            grid.toggle_cell(1, 1, CellKind::Start);
+        });
+        let carved = grid.cells.clone();
+        assert_eq!(history.position(), 3);
+        assert!(!history.record("Nothing", &mut grid, GridKind::Wall, |_| false));
+        assert_eq!(history.position(), 3);
+
+        assert_eq!(history.undo(&mut grid), Ok(Some("Carve")));
+        assert!(!grid.is_passable((0, 0), (0, 1)));
+        assert_eq!(grid.start, Some((0, 0)));
+        assert_eq!(grid.get_cell(0, 0), CellKind::Start);
+        assert_eq!(grid.get_cell(1, 1), CellKind::Empty);
+
+        history.go_to(&mut grid, 0).unwrap();
+        assert_eq!(grid.start, None);
+        assert!(grid.goals.is_empty());
+        assert!(grid.cells.iter().all(|cell| *cell == CellKind::Empty));
+        assert_eq!(history.undo(&mut grid), Ok(None));
+
+        history.go_to(&mut grid, 3).unwrap();
+        assert_eq!(grid.cells, carved);
+        assert_eq!(grid.start, Some((1, 1)));
+        assert_eq!(grid.goals, vec![(2, 2)]);
+        assert!(grid.is_passable((0, 0), (0, 1)));
+        assert_eq!(
+            history.entries().collect::<Vec<_>>(),
+            vec!["Start", "Goal", "Carve"]
+        );
+
+        // a fresh edit drops everything that was undone
+        history.undo(&mut grid).unwrap();
+        history.record("Wall", &mut grid, GridKind::Wall, |grid| {
+            grid.toggle_cell(1, 0, CellKind::Wall)
+        });
+        assert_eq!(
+            history.entries().collect::<Vec<_>>(),
+            vec!["Start", "Goal", "Wall"]
+        );
+    }
+
+    #[test]
+    fn touched_snapshots_keep_only_what_was_handed_over() {
+        let mut grid = WallGrid::with_dims(3, 3);
+        grid.toggle_cell(0, 0, CellKind::Start);
+        let mut history = History::default();
+
+        // moving the start rewrites the old start cell without it being handed over
+        let mut before = Snapshot::touched(&grid);
+        before.touch_cell(4, grid.get_cell(1, 1));
+        grid.toggle_cell(1, 1, CellKind::Start);
+        before.touch_wall((0, 1), (0, 0), grid.is_passable((0, 0), (0, 1)));
+        grid.clear_wall_between((0, 0), (0, 1)).unwrap();
+        // a second hand over of the same cell doesn't replace the first
+        before.touch_cell(4, grid.get_cell(1, 1));
+        assert!(history.commit("Move", before, &grid, GridKind::Wall));
+
+        history.undo(&mut grid).unwrap();
+        assert_eq!(grid.start, Some((0, 0)));
+        assert_eq!(grid.get_cell(0, 0), CellKind::Start);
+        assert_eq!(grid.get_cell(1, 1), CellKind::Empty);
+        assert!(!grid.is_passable((0, 0), (0, 1)));
+
+        history.redo(&mut grid).unwrap();
+        assert_eq!(grid.start, Some((1, 1)));
+        assert_eq!(grid.get_cell(0, 0), CellKind::Empty);
+        assert!(grid.is_passable((0, 0), (0, 1)));
+
+        let before = Snapshot::touched(&grid);
+        assert!(!history.commit("Nothing", before, &grid, GridKind::Wall));
+    }
+}
@@ -0,0 +1,23 @@
+// the maze engine, grids, generators, solvers and file formats with no windowing dependencies.
+// the wgpu/imgui frontend sits behind the default `gui` feature
+#[allow(dead_code)]
+pub mod analysis;
+
+pub mod cli;
+
+pub mod error;
+
+#[allow(dead_code)]
+pub mod formats;
+
+#[allow(dead_code)]
+pub mod grids;
+
+#[allow(dead_code)]
+pub mod generators;
+
+pub mod history;
+
+#[cfg(feature = "gui")]
+#[allow(dead_code)]
+pub mod frontend;
@@ -6,23 +6,34 @@ use winit::{
 };
 
 use imgui::im_str;
+use std::collections::HashMap;
 
-#[allow(dead_code)]
-mod grids;
+use maze_rs::error::MazeResult;
+use maze_rs::frontend::brush::{Changes, Stroke, Tool};
+use maze_rs::frontend::instances::{self, CellUniforms, InstanceTracker};
+use maze_rs::frontend::renderer::GraphicsContext;
+use maze_rs::frontend::view::{self, Camera};
+use maze_rs::history::{History, Snapshot};
+use maze_rs::{analysis, cli, formats, generators, grids};
 
-use grids::block_grid::BlockGrid;
-use grids::wall_grid::WallGrid;
-
-#[allow(dead_code)]
-mod renderer;
-use renderer::GraphicsContext;
-
-#[allow(dead_code)]
-mod generators;
-use generators::aldous_broder::AldousBroder;
-use generators::prim::RandPrims;
+use analysis::heatmap::Gradient;
+use analysis::stats::MazeStats;
+use analysis::validity::Validation;
+use formats::json::MazeDocument;
 use generators::{Generator, GeneratorKind};
-use grids::{CellKind, Grid, GridKind, SolverKind};
+use grids::{CellKind, Grid, GridKind, SolverKind, MAX_DIMENSION};
+
+const TEXT_PATH: &str = "maze.txt";
+const JSON_PATH: &str = "maze.json";
+const BINARY_PATH: &str = "maze.bin";
+const PNG_PATH: &str = "maze.png";
+const SVG_PATH: &str = "maze.svg";
+const GIF_PATH: &str = "maze.gif";
+const OBJ_PATH: &str = "maze.obj";
+const STL_PATH: &str = "maze.stl";
+const DOT_PATH: &str = "maze.dot";
+// zoom factor per notch of the mouse wheel
+const ZOOM_STEP: f32 = 1.2;
 
 pub struct State {
     pub gfx_ctx: GraphicsContext,
@@ -30,12 +41,79 @@ pub struct State {
     pub grid_kind: GridKind,
     pub generator_kind: GeneratorKind,
     pub maze_generator: Box<dyn Generator>,
+    pub seed: u64,
+    pub lock_seed: bool,
+    // generator and seed behind the current grid, None once the grid is replaced
+    pub generated: Option<(GeneratorKind, u64)>,
 
     pub last_x: f32,
     pub last_y: f32,
 
-    pub rows: u16,
-    pub cols: u16,
+    pub rows: u32,
+    pub cols: u32,
+
+    pub heatmap: bool,
+    pub gradient: Gradient,
+    pub show_problems: bool,
+    // per cell colours drawn instead of the CellKind palette
+    pub overlay: Option<Vec<Option<[f32; 4]>>>,
+    // grid revision, or None while neither is shown, heatmap stops and problem highlighting the
+    // overlay was built from
+    pub overlay_key: Option<(Option<u64>, Option<Vec<[f32; 4]>>, bool)>,
+    // cells a rectangle or line will paint once the mouse is released, drawn over the overlay
+    pub preview: HashMap<usize, [f32; 4]>,
+    // stroke end the preview was built for
+    pub preview_end: Option<(usize, usize)>,
+    // bumped whenever the grid changes, so anything worked out from it knows to start again
+    pub revision: u64,
+    // stats panel figures and the revision they were worked out at
+    pub stats: Option<(u64, MazeStats)>,
+    // shared by the Validate button and problem highlighting, same as stats
+    pub validation: Option<(u64, Validation)>,
+    // last thing the grid or generator refused to do, shown until dismissed
+    pub error: Option<String>,
+    // undo and redo for everything drawn or generated on the current grid
+    pub history: History,
+
+    pub tool: Tool,
+    pub erase: bool,
+    // the drag in progress and the grid from before it started
+    pub stroke: Option<(Stroke, Snapshot)>,
+    // generator or solver steps not yet in the history
+    pub run: Option<Run>,
+
+    pub camera: Camera,
+    // middle mouse held
+    pub panning: bool,
+    // which cells need writing to the gpu
+    pub instances: InstanceTracker,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RunKind {
+    Generate,
+    Solve,
+}
+
+// steps taken one click or one frame at a time, kept as a single undo entry once they stop
+pub struct Run {
+    pub kind: RunKind,
+    // stepping every frame rather than per click
+    pub expanded: bool,
+    pub before: Snapshot,
+    // the screen matches the generator, so steps only copy what they changed
+    pub synced: bool,
+}
+
+impl Run {
+    fn label(&self) -> &'static str {
+        match (self.kind, self.expanded) {
+            (RunKind::Generate, true) => "Expanded Generate",
+            (RunKind::Generate, false) => "Step Maze",
+            (RunKind::Solve, true) => "Expanded Solve",
+            (RunKind::Solve, false) => "Step Solver",
+        }
+    }
 }
 
 impl State {
@@ -43,8 +121,15 @@ impl State {
     fn input(&mut self, event: &WindowEvent, kind: CellKind) -> bool {
         match event {
             WindowEvent::CursorMoved { position, .. } => {
+                let (last_x, last_y) = (self.last_x, self.last_y);
                 self.last_x = position.x as f32 / self.gfx_ctx.size.width as f32;
                 self.last_y = position.y as f32 / self.gfx_ctx.size.height as f32;
+                if self.panning {
+                    self.camera.pan((
+                        (self.last_x - last_x) * self.gfx_ctx.size.width as f32,
+                        (self.last_y - last_y) * self.gfx_ctx.size.height as f32,
+                    ));
+                }
                 /*
                 self.gfx_ctx.clear_color = wgpu::Color {
                     r: self.last_x as f64,
@@ -53,73 +138,599 @@ impl State {
                     a: 1.0,
                 };
                 */
+                self.drag_stroke();
+                true
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Middle,
+                ..
+            } => {
+                self.panning = state == &ElementState::Pressed;
                 true
             }
             WindowEvent::MouseInput { state, .. } => {
-                if state == &ElementState::Pressed {
-                    self.grid
-                        .handle_click((self.last_x, self.last_y), self.gfx_ctx.size, kind);
+                match state {
+                    ElementState::Pressed => self.begin_stroke(kind),
+                    ElementState::Released => self.finish_stroke(),
                 }
                 true
             }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let notches = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    // touchpads scroll in pixels, roughly 40 to a notch
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / 40.0,
+                };
+                let pos = (self.last_x, self.last_y);
+                self.camera
+                    .zoom_at(pos, self.gfx_ctx.size, ZOOM_STEP.powf(notches));
+                true
+            }
             _ => false,
         }
     }
 
-    fn update(&mut self) {
-        // snap dimensions to odd values, for reasons
-        self.rows |= 1;
-        self.cols |= 1;
+    fn begin_stroke(&mut self, kind: CellKind) {
+        self.finish_stroke();
+        self.finish_run();
+        let pos = (self.last_x, self.last_y);
+        let cell = match view::cell_at(self.grid.dims(), &self.camera, pos, self.gfx_ctx.size) {
+            Some(cell) => cell,
+            None => return,
+        };
+        // block grid walls are cells, so the wall tool paints them like a brush
+        let tool = if self.tool == Tool::Walls && self.grid_kind != GridKind::Wall {
+            Tool::Brush
+        } else {
+            self.tool
+        };
+
+        let mut before = Snapshot::touched(self.grid.as_ref());
+        let (erase, columns) = (self.erase, self.grid.dims().columns);
+        // the new stroke can end where the last one did and still need its own preview
+        self.preview_end = None;
+        let mut stroke = Stroke::begin(self.grid_mut(), tool, kind, erase, cell);
+        let changes = stroke.take_changes();
+        keep_changes(&mut before, &mut self.instances, changes, columns);
+        self.stroke = Some((stroke, before));
+        self.drag_stroke();
+    }
+
+    fn drag_stroke(&mut self) {
+        let pos = (self.last_x, self.last_y);
+        let columns = self.grid.dims().columns;
+        let (stroke, before) = match self.stroke.as_mut() {
+            Some((stroke, before)) => (stroke, before),
+            None => return,
+        };
+
+        if stroke.tool == Tool::Walls {
+            if let Some(edge) =
+                view::edge_at(self.grid.dims(), &self.camera, pos, self.gfx_ctx.size)
+            {
+                let result = stroke.drag_edge(self.grid.as_mut(), edge);
+                let changes = stroke.take_changes();
+                if !changes.is_empty() {
+                    self.revision += 1;
+                }
+                keep_changes(before, &mut self.instances, changes, columns);
+                self.report(result);
+            }
+        } else if let Some(cell) =
+            view::cell_at(self.grid.dims(), &self.camera, pos, self.gfx_ctx.size)
+        {
+            stroke.drag(self.grid.as_mut(), cell);
+            // shapes only move their preview until released, which leaves the caches good
+            let changes = stroke.take_changes();
+            if !changes.is_empty() {
+                self.revision += 1;
+            }
+            keep_changes(before, &mut self.instances, changes, columns);
+        }
+    }
+
+    fn finish_stroke(&mut self) {
+        if let Some((stroke, mut before)) = self.stroke.take() {
+            let (label, columns) = (stroke.label(), self.grid.dims().columns);
+            let changes = stroke.finish(self.grid_mut());
+            keep_changes(&mut before, &mut self.instances, changes, columns);
+            self.history
+                .commit(&label, before, self.grid.as_ref(), self.grid_kind);
+        }
+    }
 
+    fn update(&mut self) {
         let rows = self.rows as usize;
         let cols = self.cols as usize;
         if rows != self.grid.dims().rows || cols != self.grid.dims().columns {
-            self.grid = new_grid(self.grid_kind, (rows, cols));
-            self.maze_generator = new_generator(self.generator_kind, self);
+            self.set_grid(self.grid_kind, grids::new_grid(self.grid_kind, rows, cols));
+        }
+
+        // the heatmap and validation walk the whole grid, so only redo them once it has changed
+        let stops = if self.heatmap {
+            Some(self.gradient.stops.clone())
+        } else {
+            None
+        };
+        // with nothing shown grid changes can't move an overlay colour
+        let active = self.heatmap || self.show_problems;
+        let key = (
+            if active { Some(self.revision) } else { None },
+            stops,
+            self.show_problems,
+        );
+        if self.overlay_key.as_ref() != Some(&key) {
+            let previous = self.overlay.take();
+            self.overlay = if self.heatmap {
+                analysis::heatmap::heatmap(self.grid.as_ref(), &self.gradient)
+            } else {
+                None
+            };
+
+            if self.show_problems {
+                let (cells, columns) = (self.grid.cells().len(), self.grid.dims().columns);
+                let problems = self.validation().overlay(cells, columns);
+                self.overlay = Some(match self.overlay.take() {
+                    Some(overlay) => overlay
+                        .into_iter()
+                        .zip(problems)
+                        .map(|(color, problem)| problem.or(color))
+                        .collect(),
+                    None => problems,
+                });
+            }
+            self.overlay_key = Some(key);
+
+            // only cells whose colour moved get rewritten
+            if previous.is_some() || self.overlay.is_some() {
+                for index in 0..self.grid.cells().len() {
+                    if overlay_color(&previous, index) != overlay_color(&self.overlay, index) {
+                        self.instances.mark(index);
+                    }
+                }
+            }
+        }
+
+        // what a rectangle or line will paint once the mouse is released, only worked out again
+        // when the stroke ends somewhere else
+        let end = self.stroke.as_ref().map(|(stroke, _)| stroke.last);
+        if end != self.preview_end {
+            self.preview_end = end;
+            let previous = std::mem::take(&mut self.preview);
+            if let Some((stroke, _)) = &self.stroke {
+                let color = if stroke.erase {
+                    CellKind::Empty.into()
+                } else {
+                    stroke.kind.into()
+                };
+                let columns = self.grid.dims().columns;
+                for (row, column) in stroke.preview() {
+                    self.preview.insert((row * columns) + column, color);
+                }
+            }
+            for index in previous.keys() {
+                if !self.preview.contains_key(index) {
+                    self.instances.mark(*index);
+                }
+            }
+            for (index, color) in &self.preview {
+                if previous.get(index) != Some(color) {
+                    self.instances.mark(*index);
+                }
+            }
         }
     }
 
-    fn render(&mut self, view: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue) {
-        self.gfx_ctx.start(view, device, queue);
+    // swaps in a loaded grid, keeping the sliders and generator in step with it. grids larger than
+    // a typed size could be are refused and false handed back
+    fn set_grid(&mut self, grid_kind: GridKind, grid: Box<dyn Grid>) -> bool {
+        let dims = grid.dims();
+        if dims.rows > MAX_DIMENSION || dims.columns > MAX_DIMENSION {
+            self.error = Some(format!(
+                "{}x{} is larger than {}x{}",
+                dims.rows, dims.columns, MAX_DIMENSION, MAX_DIMENSION
+            ));
+            // or the next update tries the same size again
+            self.rows = self.grid.dims().rows as u32;
+            self.cols = self.grid.dims().columns as u32;
+            return false;
+        }
+
+        self.rows = grid.dims().rows as u32;
+        self.cols = grid.dims().columns as u32;
+        self.grid_kind = grid_kind;
+        self.grid = grid;
+        self.maze_generator = new_generator(self.generator_kind, self);
+        self.generated = None;
+        self.history.clear();
+        self.stroke = None;
+        self.run = None;
+        self.instances.invalidate();
+        self.revision += 1;
+        self.fit_camera();
+        true
+    }
+
+    // worked out again only once the grid has changed
+    fn stats(&mut self) -> &MazeStats {
+        if self.stats.as_ref().map(|(revision, _)| *revision) != Some(self.revision) {
+            self.stats = None;
+        }
+        let (revision, grid) = (self.revision, self.grid.as_ref());
+        &self
+            .stats
+            .get_or_insert_with(|| (revision, MazeStats::from_grid(grid)))
+            .1
+    }
+
+    fn validation(&mut self) -> &Validation {
+        if self.validation.as_ref().map(|(revision, _)| *revision) != Some(self.revision) {
+            self.validation = None;
+        }
+        let (revision, grid) = (self.revision, self.grid.as_ref());
+        &self
+            .validation
+            .get_or_insert_with(|| (revision, analysis::validity::validate(grid)))
+            .1
+    }
+
+    fn fit_camera(&mut self) {
+        self.camera = Camera::fit(self.grid.dims(), self.gfx_ctx.size);
+    }
+
+    // for edits that mark the cells they change themselves
+    fn grid_mut(&mut self) -> &mut dyn Grid {
+        self.revision += 1;
+        self.grid.as_mut()
+    }
+
+    // typed sizes are clamped, and kept odd for block grids like the sliders
+    fn set_dims(&mut self, rows: i32, cols: i32) {
+        let snap = self.grid_kind == GridKind::Block;
+        let clamp = |value: i32| {
+            let value = (value.max(3) as u32).min(MAX_DIMENSION as u32);
+            // rounding up to odd mustn't go past the largest size
+            match value | 1 {
+                odd if snap && odd > MAX_DIMENSION as u32 => odd - 2,
+                odd if snap => odd,
+                _ => value,
+            }
+        };
+        self.rows = clamp(rows);
+        self.cols = clamp(cols);
+    }
+
+    // redraws the current maze in the other style, staying put if it can't be converted
+    fn convert_grid(&mut self, from: GridKind, to: GridKind) {
+        let result = grids::convert::convert_grid(self.grid.as_ref(), from, to);
+        let converted = match self.report(result) {
+            Some(grid) => self.set_grid(to, grid),
+            None => false,
+        };
+        if !converted {
+            self.grid_kind = from;
+        }
+    }
+
+    // fresh generator for the next maze, rolling a new seed unless it is locked
+    fn restart_generator(&mut self) {
+        if !self.lock_seed {
+            self.seed = rand::random();
+        }
+        self.maze_generator = new_generator(self.generator_kind, self);
+        self.generated = Some((self.generator_kind, self.seed));
+    }
+
+    // hands back the value, or keeps the error for the controls window
+    fn report<T>(&mut self, result: MazeResult<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.error = Some(e.to_string());
+                None
+            }
+        }
+    }
+
+    // logs the file that was written, or keeps why it couldn't be for the controls window
+    fn wrote<T>(&mut self, path: &str, result: std::io::Result<T>) {
+        match result {
+            Ok(_) => log::info!("wrote {}", path),
+            Err(e) => self.error = Some(format!("failed to write {}: {}", path, e)),
+        }
+    }
+
+    // hands back what was read, or keeps why it couldn't be for the controls window
+    fn read<T>(&mut self, path: &str, result: std::io::Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                log::info!("read {}", path);
+                Some(value)
+            }
+            Err(e) => {
+                self.error = Some(format!("failed to read {}: {}", path, e));
+                None
+            }
+        }
+    }
+
+    // runs an edit and keeps whatever it changed on the grid for undo
+    fn edit<T>(&mut self, label: &str, edit: impl FnOnce(&mut State) -> T) -> T {
+        self.finish_run();
+        let before = Snapshot::of(self.grid.as_ref(), self.grid_kind);
+        let value = edit(self);
+        // fill and generate move walls anywhere, so every cell is rebuilt
+        self.instances.invalidate();
+        self.revision += 1;
+        self.history
+            .commit(label, before, self.grid.as_ref(), self.grid_kind);
+        value
+    }
+
+    // like edit, for edits that only move the start and goals around
+    fn edit_ends<T>(&mut self, label: &str, edit: impl FnOnce(&mut dyn Grid) -> T) -> T {
+        self.finish_run();
+        let before = Snapshot::touched(self.grid.as_ref());
+        let mut ends = end_indices(self.grid.as_ref());
+        let value = edit(self.grid.as_mut());
+        ends.extend(end_indices(self.grid.as_ref()));
+        for index in ends {
+            self.instances.mark(index);
+        }
+        self.revision += 1;
+        self.history
+            .commit(label, before, self.grid.as_ref(), self.grid_kind);
+        value
+    }
+
+    fn undo(&mut self) {
+        self.finish_run();
+        self.instances.invalidate();
+        self.revision += 1;
+        let result = self.history.undo(self.grid.as_mut()).map(|_| ());
+        self.report(result);
+    }
+
+    fn redo(&mut self) {
+        self.finish_run();
+        self.instances.invalidate();
+        self.revision += 1;
+        let result = self.history.redo(self.grid.as_mut()).map(|_| ());
+        self.report(result);
+    }
+
+    fn go_to(&mut self, position: usize) {
+        self.finish_run();
+        self.instances.invalidate();
+        self.revision += 1;
+        let result = self.history.go_to(self.grid.as_mut(), position);
+        self.report(result);
+    }
+
+    // carries on with a run of the same kind, otherwise puts the last one in the history and
+    // starts a new one from here
+    fn begin_run(&mut self, kind: RunKind, expanded: bool) {
+        match &self.run {
+            Some(run) if run.kind == kind && run.expanded == expanded => {}
+            _ => {
+                self.finish_run();
+                self.run = Some(Run {
+                    kind,
+                    expanded,
+                    before: Snapshot::of(self.grid.as_ref(), self.grid_kind),
+                    synced: false,
+                });
+            }
+        }
+    }
+
+    fn finish_run(&mut self) {
+        if let Some(run) = self.run.take() {
+            self.history
+                .commit(run.label(), run.before, self.grid.as_ref(), self.grid_kind);
+        }
+    }
+
+    fn is_running(&self, kind: RunKind) -> bool {
+        match &self.run {
+            Some(run) => run.kind == kind && run.expanded,
+            None => false,
+        }
+    }
+
+    // one generator or solver step, false once there is nothing left to do
+    fn step(&mut self, kind: RunKind) -> bool {
+        match kind {
+            RunKind::Generate => {
+                let synced = self.run.as_ref().map_or(false, |run| run.synced);
+                let result = if synced {
+                    self.step_generated()
+                } else {
+                    self.copy_generated(false)
+                };
+                let stepped = self.report(result).is_some();
+                if let Some(run) = self.run.as_mut() {
+                    run.synced = stepped;
+                }
+                stepped && !self.maze_generator.is_done()
+            }
+            RunKind::Solve => {
+                let mut changed = Vec::new();
+                let result = self.grid_mut().step_solve_path(&mut changed);
+                for index in changed {
+                    self.instances.mark(index);
+                }
+                self.report(result).unwrap_or(false)
+            }
+        }
+    }
 
-        let verts = self.grid.render(self);
+    // a step by hand, the run only ends with the maze or solve or when something else is done
+    fn step_once(&mut self, kind: RunKind) {
+        self.begin_run(kind, false);
+        if !self.step(kind) {
+            self.finish_run();
+        }
+    }
 
-        self.gfx_ctx.draw(&verts, view, device);
+    // expanded runs take a step every frame
+    fn step_run(&mut self) {
+        let kind = match &self.run {
+            Some(run) if run.expanded => run.kind,
+            _ => return,
+        };
+        if !self.step(kind) {
+            self.finish_run();
+        }
+    }
+
+    // copies the generator's maze onto the screen, either one more step of it or all of it
+    fn copy_generated(&mut self, finish: bool) -> MazeResult<()> {
+        let gen_grid = if finish {
+            self.maze_generator.generate_maze()?
+        } else {
+            self.maze_generator.next_step()?
+        };
+        self.instances.invalidate();
+        self.revision += 1;
+        self.grid.set_cells(gen_grid.cells().clone());
+        self.grid.set_paths(gen_grid.paths()?)
+    }
+
+    // one more generator step, copying over only the cells it changed and the walls beside them
+    fn step_generated(&mut self) -> MazeResult<()> {
+        self.maze_generator.step_generation()?;
+        let generated = self.maze_generator.grid();
+        let dims = generated.dims();
+        for &(row, column) in self.maze_generator.changed() {
+            self.grid
+                .set_cell(row, column, generated.get_cell(row, column));
+            self.instances.mark((row * dims.columns) + column);
+            if self.grid_kind != GridKind::Wall {
+                continue;
+            }
+
+            let cell = (row, column);
+            let neighbors = [
+                (row + 1 < dims.rows, (row + 1, column)),
+                (row > 0, (row.wrapping_sub(1), column)),
+                (column + 1 < dims.columns, (row, column + 1)),
+                (column > 0, (row, column.wrapping_sub(1))),
+            ];
+            for &(_, neighbor) in neighbors.iter().filter(|(exists, _)| *exists) {
+                let open = generated.is_passable(cell, neighbor);
+                if open == self.grid.is_passable(cell, neighbor) {
+                    continue;
+                }
+                if open {
+                    self.grid.clear_wall_between(cell, neighbor)?;
+                } else {
+                    self.grid.add_wall_between(cell, neighbor)?;
+                }
+                // the wall is drawn by whichever cell is west or south of it
+                self.instances
+                    .mark((neighbor.0 * dims.columns) + neighbor.1);
+            }
+        }
+        self.revision += 1;
+        Ok(())
+    }
+
+    fn render(&mut self, view: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let (overlay, preview) = (&self.overlay, &self.preview);
+        let changed = self
+            .instances
+            .update(self.grid.as_ref(), self.grid_kind, |index, kind| {
+                cell_color(overlay, preview, index, kind)
+            });
+        self.gfx_ctx
+            .upload_instances(self.instances.instances(), &changed, device, queue);
+
+        let (dims, size) = (self.grid.dims(), self.gfx_ctx.size);
+        self.gfx_ctx
+            .set_cell_uniforms(&CellUniforms::new(&self.camera, dims, size), queue);
+        let visible = instances::visible_instances(&self.camera, dims, size);
+        self.gfx_ctx.draw_cells(view, visible, device);
 
         self.gfx_ctx.render(queue);
     }
 }
 
-fn new_generator(generator_kind: GeneratorKind, state: &State) -> Box<dyn Generator> {
-    match generator_kind {
-        GeneratorKind::AldousBroder => Box::new(AldousBroder::new(
-            state.grid.dims().rows,
-            state.grid.dims().columns,
-            state.grid_kind,
-        )),
-
-        GeneratorKind::RandPrims => Box::new(RandPrims::new(
-            state.grid.dims().rows,
-            state.grid.dims().columns,
-            state.grid_kind,
-        )),
+fn end_indices(grid: &dyn Grid) -> Vec<usize> {
+    let columns = grid.dims().columns;
+    grid.start()
+        .into_iter()
+        .chain(grid.goals())
+        .map(|(row, column)| (row * columns) + column)
+        .collect()
+}
+
+// hands what a stroke changed over to the snapshot from before it for undo, and has those cells
+// redrawn
+fn keep_changes(
+    before: &mut Snapshot,
+    instances: &mut InstanceTracker,
+    changes: Changes,
+    columns: usize,
+) {
+    for ((row, column), kind) in changes.cells {
+        before.touch_cell((row * columns) + column, kind);
+        instances.mark((row * columns) + column);
+    }
+    for ((one, two), open) in changes.walls {
+        before.touch_wall(one, two, open);
+        instances.mark((one.0 * columns) + one.1);
+        instances.mark((two.0 * columns) + two.1);
     }
 }
-fn new_grid(grid_kind: GridKind, dims: (usize, usize)) -> Box<dyn Grid> {
-    match grid_kind {
-        GridKind::Block => Box::new(BlockGrid::with_dims(dims.0, dims.1)),
-        GridKind::Wall => Box::new(WallGrid::with_dims(dims.0, dims.1)),
+
+// per cell colours from the preview then the overlay, falling back to the CellKind palette
+fn cell_color(
+    overlay: &Option<Vec<Option<[f32; 4]>>>,
+    preview: &HashMap<usize, [f32; 4]>,
+    index: usize,
+    kind: CellKind,
+) -> [f32; 4] {
+    match preview.get(&index) {
+        Some(color) => *color,
+        None => overlay_color(overlay, index).unwrap_or_else(|| kind.into()),
     }
 }
 
+fn overlay_color(overlay: &Option<Vec<Option<[f32; 4]>>>, index: usize) -> Option<[f32; 4]> {
+    overlay
+        .as_ref()
+        .and_then(|overlay| overlay.get(index).copied().flatten())
+}
+
+fn new_generator(generator_kind: GeneratorKind, state: &State) -> Box<dyn Generator> {
+    generators::new_generator(
+        generator_kind,
+        state.grid.dims().rows,
+        state.grid.dims().columns,
+        state.grid_kind,
+        state.seed,
+    )
+}
+
 fn main() {
     env_logger::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(result) = cli::run(&args) {
+        if let Err(e) = result {
+            eprintln!("maze-rs: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new().build(&event_loop).unwrap();
     let hidpi_factor = window.scale_factor();
     // Since main can't be async, we're going to need to block
     let grid_kind = GridKind::Wall;
-    let grid = new_grid(grid_kind, (17, 17));
+    let grid = grids::new_grid(grid_kind, 17, 17);
 
     let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
     let surface = unsafe { instance.create_surface(&window) };
@@ -185,30 +796,60 @@ fn main() {
         imgui_wgpu::Renderer::new_glsl(&mut imgui, &device, &mut queue, sc_desc.format);
 
     let generator_kind = GeneratorKind::RandPrims;
-    let maze_generator = Box::new(RandPrims::new(
+    let seed = rand::random();
+    let maze_generator = generators::new_generator(
+        generator_kind,
         grid.dims().rows,
         grid.dims().columns,
         grid_kind,
-    ));
+        seed,
+    );
 
+    let camera = Camera::fit(grid.dims(), gfx_ctx.size);
     let mut state = State {
         gfx_ctx,
-        rows: grid.dims().rows as u16,
-        cols: grid.dims().columns as u16,
+        rows: grid.dims().rows as u32,
+        cols: grid.dims().columns as u32,
         generator_kind,
         maze_generator,
+        seed,
+        lock_seed: false,
+        generated: None,
         grid,
         grid_kind,
         last_x: 0.0,
         last_y: 0.0,
+        heatmap: false,
+        gradient: Gradient::default(),
+        show_problems: false,
+        overlay: None,
+        overlay_key: None,
+        preview: HashMap::new(),
+        preview_end: None,
+        revision: 0,
+        stats: None,
+        validation: None,
+        error: None,
+        history: History::default(),
+        tool: Tool::Brush,
+        erase: false,
+        stroke: None,
+        run: None,
+        camera,
+        panning: false,
+        instances: InstanceTracker::default(),
     };
 
     let mut last_frame = std::time::Instant::now();
     let mut last_cursor = None;
     let mut show_demo = false;
+    let mut show_stats = false;
+    let mut modifiers = ModifiersState::empty();
     let mut grid_kind = CellKind::Start;
-    let mut expanded_solve_running = false;
-    let mut expanded_gen_running = false;
+    let mut place_diameter_ends = true;
+    let mut validation_text = String::new();
+    let mut unicode_text = false;
+    let mut contract_dot = false;
 
     event_loop.run(move |event, _, control_flow| {
         match event {
@@ -252,6 +893,13 @@ fn main() {
                     window
                         .size([300.0, 400.0], imgui::Condition::FirstUseEver)
                         .build(&ui, || {
+                            if let Some(error) = state.error.clone() {
+                                ui.text_colored([1.0, 0.3, 0.3, 1.0], error);
+                                if ui.small_button(im_str!("Dismiss")) {
+                                    state.error = None;
+                                }
+                                ui.separator();
+                            }
                             ui.text(im_str!("Frametime: {:?}", delta_s));
                             ui.separator();
                             let mouse_pos = ui.io().mouse_pos;
@@ -265,40 +913,66 @@ fn main() {
                             if ui.button(im_str!("Toggle Demo"), [100., 20.]) {
                                 show_demo = !show_demo
                             }
+                            ui.same_line(150.);
+                            if ui.button(im_str!("Toggle Stats"), [100., 20.]) {
+                                show_stats = !show_stats
+                            }
+                            // wheel zooms about the cursor, middle drag pans
+                            if ui.button(im_str!("Fit to Window"), [250., 20.]) {
+                                state.fit_camera();
+                            }
 
                             ui.separator();
 
+                            // the same maze either way round, a wall grid of RxC is drawn as a
+                            // (2R+1)x(2C+1) block grid
+                            let previous_kind = state.grid_kind;
                             if ui.radio_button(
                                 im_str!("Block Grid"),
                                 &mut state.grid_kind,
                                 GridKind::Block,
-                            ) {
-                                state.grid_kind = GridKind::Block;
-                                let dims = state.grid.dims();
-                                state.grid = new_grid(state.grid_kind, (dims.rows, dims.columns));
-                                state.maze_generator = new_generator(state.generator_kind, &state);
+                            ) && previous_kind != GridKind::Block
+                            {
+                                state.convert_grid(previous_kind, GridKind::Block);
                             }
                             ui.same_line(150.);
                             if ui.radio_button(
                                 im_str!("Wall Grid"),
                                 &mut state.grid_kind,
                                 GridKind::Wall,
-                            ) {
-                                state.grid_kind = GridKind::Wall;
-                                let dims = state.grid.dims();
-                                state.grid = new_grid(state.grid_kind, (dims.rows, dims.columns));
-                                state.maze_generator = new_generator(state.generator_kind, &state);
+                            ) && previous_kind != GridKind::Wall
+                            {
+                                state.convert_grid(previous_kind, GridKind::Wall);
                             }
 
                             ui.separator();
 
-                            imgui::Slider::new(im_str!("rows"))
+                            // block grids snap to odd sizes so their walls and cells line up
+                            let snap = state.grid_kind == GridKind::Block;
+                            if imgui::Slider::new(im_str!("rows"))
                                 .range(3..=255)
-                                .build(&ui, &mut state.rows);
+                                .build(&ui, &mut state.rows)
+                                && snap
+                            {
+                                state.rows |= 1;
+                            }
 
-                            imgui::Slider::new(im_str!("columns"))
+                            if imgui::Slider::new(im_str!("columns"))
                                 .range(3..=255)
-                                .build(&ui, &mut state.cols);
+                                .build(&ui, &mut state.cols)
+                                && snap
+                            {
+                                state.cols |= 1;
+                            }
+                            // past the sliders, applied on enter
+                            let mut typed = [state.rows as i32, state.cols as i32];
+                            if ui
+                                .input_int2(im_str!("rows, columns"), &mut typed)
+                                .enter_returns_true(true)
+                                .build()
+                            {
+                                state.set_dims(typed[0], typed[1]);
+                            }
 
                             ui.separator();
 
@@ -315,13 +989,181 @@ fn main() {
                                 grid_kind = CellKind::Wall;
                             }
 
+                            ui.radio_button(im_str!("Brush"), &mut state.tool, Tool::Brush);
+                            ui.same_line(100.);
+                            ui.radio_button(im_str!("Rectangle"), &mut state.tool, Tool::Rectangle);
+                            ui.same_line(200.);
+                            ui.radio_button(im_str!("Line"), &mut state.tool, Tool::Line);
+                            // walls between cells only exist on wall grids
+                            if state.grid_kind == GridKind::Wall {
+                                ui.radio_button(im_str!("Walls"), &mut state.tool, Tool::Walls);
+                                ui.same_line(100.);
+                            }
+                            ui.checkbox(im_str!("Erase"), &mut state.erase);
+                            let mut multi_goal = state.grid.multi_goal();
+                            if ui.checkbox(im_str!("Multi Goal"), &mut multi_goal) {
+                                state.edit_ends("Multi Goal", |grid| {
+                                    grid.set_multi_goal(multi_goal)
+                                });
+                            }
+
+                            ui.separator();
+                            if ui.button(im_str!("Export Text"), [125., 20.]) {
+                                let style = if unicode_text {
+                                    formats::text::WallStyle::Unicode
+                                } else {
+                                    formats::text::WallStyle::Ascii
+                                };
+                                let text = formats::text::to_text(
+                                    state.grid.as_ref(),
+                                    state.grid_kind,
+                                    style,
+                                );
+                                let result = std::fs::write(TEXT_PATH, text);
+                                state.wrote(TEXT_PATH, result);
+                            }
+                            ui.same_line(150.);
+                            if ui.button(im_str!("Import Text"), [125., 20.]) {
+                                let result = std::fs::read_to_string(TEXT_PATH)
+                                    .and_then(|text| formats::text::parse(&text));
+                                if let Some((grid_kind, grid)) = state.read(TEXT_PATH, result) {
+                                    state.set_grid(grid_kind, grid);
+                                }
+                            }
+                            ui.checkbox(im_str!("Unicode Walls"), &mut unicode_text);
+                            if ui.button(im_str!("Export PNG"), [125., 20.]) {
+                                let result = std::fs::File::create(PNG_PATH).and_then(|file| {
+                                    formats::raster::write_png(
+                                        state.grid.as_ref(),
+                                        state.grid_kind,
+                                        &formats::raster::RasterOptions::default(),
+                                        std::io::BufWriter::new(file),
+                                    )
+                                });
+                                state.wrote(PNG_PATH, result);
+                            }
+                            ui.same_line(150.);
+                            if ui.button(im_str!("Export SVG"), [125., 20.]) {
+                                let result = std::fs::File::create(SVG_PATH).and_then(|file| {
+                                    formats::svg::write_svg(
+                                        state.grid.as_ref(),
+                                        state.grid_kind,
+                                        &formats::raster::RasterOptions::default(),
+                                        std::io::BufWriter::new(file),
+                                    )
+                                });
+                                state.wrote(SVG_PATH, result);
+                            }
+                            if ui.button(im_str!("Export Mesh"), [125., 20.]) {
+                                let mesh = formats::mesh::Mesh::from_grid(
+                                    state.grid.as_ref(),
+                                    state.grid_kind,
+                                    &formats::mesh::MeshOptions::default(),
+                                );
+                                let result = std::fs::File::create(OBJ_PATH)
+                                    .and_then(|file| mesh.write_obj(std::io::BufWriter::new(file)));
+                                state.wrote(OBJ_PATH, result);
+                                let result = std::fs::File::create(STL_PATH)
+                                    .and_then(|file| mesh.write_stl(std::io::BufWriter::new(file)));
+                                state.wrote(STL_PATH, result);
+                            }
+                            ui.same_line(150.);
+                            if ui.button(im_str!("Export DOT"), [125., 20.]) {
+                                let options = formats::dot::DotOptions {
+                                    contract_corridors: contract_dot,
+                                    ..formats::dot::DotOptions::default()
+                                };
+                                let result = std::fs::File::create(DOT_PATH).and_then(|file| {
+                                    formats::dot::write_dot(
+                                        state.grid.as_ref(),
+                                        &options,
+                                        std::io::BufWriter::new(file),
+                                    )
+                                });
+                                state.wrote(DOT_PATH, result);
+                            }
+                            ui.checkbox(im_str!("Contract Corridors"), &mut contract_dot);
+                            if ui.button(im_str!("Save"), [125., 20.]) {
+                                let document = MazeDocument::from_grid(
+                                    state.grid.as_ref(),
+                                    state.grid_kind,
+                                    state.generated,
+                                );
+                                let result = std::fs::File::create(JSON_PATH)
+                                    .and_then(|file| document.write(file));
+                                state.wrote(JSON_PATH, result);
+                            }
+                            ui.same_line(150.);
+                            if ui.button(im_str!("Load"), [125., 20.]) {
+                                let result = std::fs::File::open(JSON_PATH)
+                                    .and_then(MazeDocument::read)
+                                    .and_then(|document| Ok((document.to_grid()?, document)));
+                                if let Some((grid, document)) = state.read(JSON_PATH, result) {
+                                    if state.set_grid(document.grid_kind, grid) {
+                                        if let Some(generator_kind) = document.generator {
+                                            state.generator_kind = generator_kind;
+                                        }
+                                        if let Some(seed) = document.seed {
+                                            state.seed = seed;
+                                        }
+                                        state.maze_generator =
+                                            new_generator(state.generator_kind, &state);
+                                        state.generated = document.generator.and_then(|kind| {
+                                            document.seed.map(|seed| (kind, seed))
+                                        });
+                                    }
+                                }
+                            }
+                            if ui.button(im_str!("Save Binary"), [125., 20.]) {
+                                let mut writer = formats::binary::MazeWriter::new(Vec::new());
+                                let result = writer
+                                    .write_grid(state.grid.as_ref(), state.grid_kind)
+                                    .and_then(|_| std::fs::write(BINARY_PATH, writer.into_inner()));
+                                state.wrote(BINARY_PATH, result);
+                            }
+                            ui.same_line(150.);
+                            if ui.button(im_str!("Load Binary"), [125., 20.]) {
+                                let result = std::fs::File::open(BINARY_PATH).and_then(|file| {
+                                    formats::binary::MazeReader::new(std::io::BufReader::new(file))
+                                        .read_grid()
+                                });
+                                match state.read(BINARY_PATH, result) {
+                                    Some(Some((grid_kind, grid))) => {
+                                        state.set_grid(grid_kind, grid);
+                                    }
+                                    Some(None) => {
+                                        state.error =
+                                            Some(format!("{} holds no mazes", BINARY_PATH))
+                                    }
+                                    None => {}
+                                }
+                            }
+
                             ui.separator();
                             if ui.button(im_str!("Clear Grid"), [125., 20.]) {
-                                state.grid.clear();
+                                state.edit("Clear Grid", |state| state.grid.clear());
                             }
                             ui.same_line(150.);
                             if ui.button(im_str!("Fill Grid"), [125., 20.]) {
-                                state.grid.fill();
+                                state.edit("Fill Grid", |state| state.grid.fill());
+                            }
+
+                            ui.separator();
+
+                            ui.checkbox(im_str!("Distance Heatmap"), &mut state.heatmap);
+                            if state.heatmap {
+                                imgui::ColorEdit::new(
+                                    im_str!("near"),
+                                    &mut state.gradient.stops[0],
+                                )
+                                .build(&ui);
+                                imgui::ColorEdit::new(
+                                    im_str!("middle"),
+                                    &mut state.gradient.stops[1],
+                                )
+                                .build(&ui);
+                                imgui::ColorEdit::new(im_str!("far"), &mut state.gradient.stops[2])
+                                    .build(&ui);
                             }
 
                             ui.separator();
@@ -332,6 +1174,7 @@ fn main() {
                                 GeneratorKind::RandPrims,
                             ) {
                                 state.generator_kind = GeneratorKind::RandPrims;
+                                state.finish_run();
                                 state.maze_generator = new_generator(state.generator_kind, &state);
                             }
                             ui.same_line(100.);
@@ -341,30 +1184,46 @@ fn main() {
                                 GeneratorKind::AldousBroder,
                             ) {
                                 state.generator_kind = GeneratorKind::AldousBroder;
+                                state.finish_run();
                                 state.maze_generator = new_generator(state.generator_kind, &state);
                             }
                             ui.separator();
+                            ui.text(im_str!("Seed: {}", state.seed));
+                            ui.same_line(200.);
+                            ui.checkbox(im_str!("Lock Seed"), &mut state.lock_seed);
                             if ui.button(im_str!("Generate Maze"), [250., 20.]) {
-                                state.maze_generator = new_generator(state.generator_kind, &state);
-                                let gen_grid = state.maze_generator.generate_maze();
-                                state.grid.set_cells(gen_grid.cells().clone());
-                                match state.grid_kind {
-                                    GridKind::Block => {}
-                                    GridKind::Wall => state.grid.set_paths(gen_grid.paths()),
-                                }
+                                state.restart_generator();
+                                let result =
+                                    state.edit("Generate Maze", |state| state.copy_generated(true));
+                                state.report(result);
                             }
                             ui.separator();
                             if ui.button(im_str!("Expanded Generate"), [125., 20.]) {
-                                if state.maze_generator.is_done() {
-                                    state.maze_generator =
-                                        new_generator(state.generator_kind, &state);
+                                if state.is_running(RunKind::Generate) {
+                                    state.finish_run();
+                                } else {
+                                    if state.maze_generator.is_done() {
+                                        state.restart_generator();
+                                    }
+                                    state.begin_run(RunKind::Generate, true);
                                 }
-                                expanded_gen_running = !expanded_gen_running;
                             }
                             ui.same_line(150.);
                             if ui.button(im_str!("Step Maze"), [125., 20.]) {
-                                let gen_grid = state.maze_generator.next_step();
-                                state.grid.set_cells(gen_grid.cells().clone());
+                                state.step_once(RunKind::Generate);
+                            }
+                            // replays the current seed, so this records the last generated maze
+                            if ui.button(im_str!("Record GIF"), [125., 20.]) {
+                                let mut generator = new_generator(state.generator_kind, &state);
+                                let result = std::fs::File::create(GIF_PATH).and_then(|file| {
+                                    formats::animation::record_generation(
+                                        generator.as_mut(),
+                                        state.grid_kind,
+                                        formats::animation::AnimationOptions::default(),
+                                        std::io::BufWriter::new(file),
+                                    )
+                                });
+                                state.wrote(GIF_PATH, result);
                             }
 
                             ui.separator();
@@ -396,38 +1255,187 @@ fn main() {
                             ui.separator();
 
                             if ui.button(im_str!("Solve!"), [250., 20.]) {
-                                state.grid.reset_solver();
-                                state.grid.solve_path();
+                                let result = state.edit("Solve", |state| {
+                                    if !state.grid.multi_goal() {
+                                        return state.grid.solve_path();
+                                    }
+                                    match analysis::tour::solve_tour(state.grid.as_ref()) {
+                                        Some(tour) => {
+                                            log::debug!(
+                                                "tour found: {:?}, length {}",
+                                                tour.order,
+                                                tour.length
+                                            );
+                                            analysis::tour::mark_tour(state.grid.as_mut(), &tour);
+                                        }
+                                        None => {
+                                            state.error = Some(
+                                                "no tour from the start reaches every goal"
+                                                    .to_string(),
+                                            )
+                                        }
+                                    }
+                                    Ok(())
+                                });
+                                state.report(result);
+                            }
+                            ui.separator();
+                            if ui.button(im_str!("Find Longest Path"), [125., 20.]) {
+                                match analysis::diameter::find_diameter(state.grid.as_ref()) {
+                                    Some(diameter) => {
+                                        log::debug!(
+                                            "longest path: {:?} to {:?}, length {}",
+                                            diameter.ends.0,
+                                            diameter.ends.1,
+                                            diameter.length
+                                        );
+                                        if place_diameter_ends {
+                                            state.edit("Place Ends", |state| {
+                                                analysis::diameter::place_ends(
+                                                    state.grid.as_mut(),
+                                                    &diameter,
+                                                )
+                                            });
+                                        }
+                                    }
+                                    None => {
+                                        state.error =
+                                            Some("there are no open cells to measure".to_string())
+                                    }
+                                }
+                            }
+                            ui.same_line(150.);
+                            ui.checkbox(im_str!("Place Start/Goal"), &mut place_diameter_ends);
+                            ui.separator();
+                            if ui.button(im_str!("Validate"), [125., 20.]) {
+                                let validation = state.validation();
+                                validation_text = format!(
+                                    "{}, {} region(s), {} loop(s), solvable: {}",
+                                    if validation.is_perfect() {
+                                        "perfect"
+                                    } else {
+                                        "not perfect"
+                                    },
+                                    validation.regions.len(),
+                                    validation.loop_edges.len(),
+                                    match validation.solvable {
+                                        Some(true) => "yes",
+                                        Some(false) => "no",
+                                        None => "no start/goal",
+                                    }
+                                );
+                            }
+                            ui.same_line(150.);
+                            ui.checkbox(im_str!("Highlight Problems"), &mut state.show_problems);
+                            if !validation_text.is_empty() {
+                                ui.text_wrapped(&imgui::ImString::new(validation_text.clone()));
                             }
                             ui.separator();
                             if ui.button(im_str!("Expanded Solve"), [125., 20.]) {
-                                state.grid.reset_solver();
-                                expanded_solve_running = !expanded_solve_running;
+                                if state.is_running(RunKind::Solve) {
+                                    state.finish_run();
+                                } else {
+                                    state.begin_run(RunKind::Solve, true);
+                                    // wall grids wipe whatever the last solve explored, anywhere
+                                    state.instances.invalidate();
+                                    let result = state.grid_mut().reset_solver();
+                                    if state.report(result).is_none() {
+                                        state.finish_run();
+                                    }
+                                }
                             }
                             ui.same_line(150.);
                             if ui.button(im_str!("Step Solver"), [125., 20.]) {
-                                state.grid.step_solve_path();
+                                state.step_once(RunKind::Solve);
                             }
                         });
 
+                    // clicking an entry undoes or redoes everything up to and including it
+                    let entries: Vec<String> = state.history.entries().map(String::from).collect();
+                    imgui::Window::new(im_str!("History"))
+                        .size([250.0, 250.0], imgui::Condition::FirstUseEver)
+                        .position([0.0, 410.0], imgui::Condition::FirstUseEver)
+                        .build(&ui, || {
+                            if ui.button(im_str!("Undo"), [100., 20.]) {
+                                state.undo();
+                            }
+                            ui.same_line(125.);
+                            if ui.button(im_str!("Redo"), [100., 20.]) {
+                                state.redo();
+                            }
+                            ui.separator();
+                            let position = state.history.position();
+                            let mut target = None;
+                            if imgui::Selectable::new(im_str!("Initial"))
+                                .selected(position == 0)
+                                .build(&ui)
+                            {
+                                target = Some(0);
+                            }
+                            for (index, entry) in entries.iter().enumerate() {
+                                let label = imgui::ImString::new(format!("{}##{}", entry, index));
+                                if imgui::Selectable::new(&label)
+                                    .selected(position == index + 1)
+                                    .build(&ui)
+                                {
+                                    target = Some(index + 1);
+                                }
+                            }
+                            if let Some(target) = target {
+                                state.go_to(target);
+                            }
+                        });
                     if show_demo {
                         ui.show_demo_window(&mut false);
                     }
-                }
 
-                if expanded_solve_running {
-                    expanded_solve_running = state.grid.step_solve_path();
+                    if show_stats {
+                        let stats = state.stats();
+                        imgui::Window::new(im_str!("Maze Stats"))
+                            .size([250.0, 300.0], imgui::Condition::FirstUseEver)
+                            .position([320.0, 0.0], imgui::Condition::FirstUseEver)
+                            .build(&ui, || {
+                                ui.text(im_str!("Open cells: {}", stats.open_cells));
+                                ui.text(im_str!(
+                                    "Dead ends: {} ({:.1}%)",
+                                    stats.dead_ends,
+                                    stats.dead_end_percent
+                                ));
+                                ui.text(im_str!("Junctions: {}", stats.junctions));
+                                ui.text(im_str!("Branches: {}", stats.branches));
+                                ui.text(im_str!("River factor: {:.2}", stats.river_factor));
+                                ui.separator();
+                                match (stats.solution_length, stats.solution_ratio) {
+                                    (Some(length), Some(ratio)) => ui.text(im_str!(
+                                        "Solution: {} steps ({:.2}x manhattan)",
+                                        length,
+                                        ratio
+                                    )),
+                                    (Some(length), None) => {
+                                        ui.text(im_str!("Solution: {} steps", length))
+                                    }
+                                    _ => ui.text(im_str!("Solution: none")),
+                                }
+                                ui.separator();
+                                ui.text(im_str!("Longest corridor: {}", stats.longest_corridor));
+                                for (length, count) in &stats.corridor_lengths {
+                                    ui.text(im_str!("  length {}: {}", length, count));
+                                }
+                            });
+                    }
                 }
 
-                if expanded_gen_running {
-                    let gen_grid = state.maze_generator.next_step();
-                    state.grid.set_cells(gen_grid.cells().clone());
-                    match state.grid_kind {
-                        GridKind::Block => (),
-                        GridKind::Wall => state.grid.set_paths(gen_grid.paths()),
-                    }
-                    expanded_gen_running = !state.maze_generator.is_done();
+                // a release over the imgui windows never reaches input, so the buttons are watched
+                // here instead
+                let held = [imgui::MouseButton::Left, imgui::MouseButton::Right];
+                if state.stroke.is_some() && !held.iter().any(|button| ui.is_mouse_down(*button)) {
+                    state.finish_stroke();
                 }
+                if !ui.is_mouse_down(imgui::MouseButton::Middle) {
+                    state.panning = false;
+                }
+
+                state.step_run();
 
                 state.update();
                 state.render(&frame.output.view, &device, &queue);
@@ -469,6 +1477,29 @@ fn main() {
                 ref event,
                 window_id,
             } if window_id == window.id() => {
+                // tracked wherever the cursor is, or ctrl let go over a panel would stay held
+                if let WindowEvent::ModifiersChanged(changed) = event {
+                    modifiers = *changed;
+                }
+                // a focused text box keeps its own ctrl+z
+                if !imgui.io().want_capture_keyboard && modifiers.ctrl() {
+                    if let WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(key),
+                                ..
+                            },
+                        ..
+                    } = event
+                    {
+                        match key {
+                            VirtualKeyCode::Z => state.undo(),
+                            VirtualKeyCode::Y => state.redo(),
+                            _ => {}
+                        }
+                    }
+                }
                 if !imgui.io().want_capture_mouse && !state.input(event, grid_kind) {
                     // UPDATED!
                     match event {
@@ -0,0 +1,30 @@
+// cell.frag
+#version 450
+
+layout(location=0) in vec2 v_local;
+layout(location=1) flat in vec4 v_color;
+layout(location=2) flat in uvec2 v_walls;
+
+layout(set=0, binding=0) uniform CellUniforms {
+	vec2 center;
+	vec2 scale;
+	uint columns;
+	float gap;
+};
+
+layout(location=0) out vec4 f_color;
+
+void main() {
+	bool east = v_local.x > 1.0 - gap;
+	bool north = v_local.y > 1.0 - gap;
+	// the corner between four cells is left to the background
+	if (east && north) {
+		discard;
+	}
+
+	uint wall = east ? v_walls.x : (north ? v_walls.y : 1u);
+	if (wall == 0u) {
+		discard;
+	}
+	f_color = wall == 2u ? vec4(0.0, 0.0, 0.0, 1.0) : v_color;
+}
@@ -0,0 +1,29 @@
+// cell.vert
+#version 450
+
+// one corner of the unit quad each cell is drawn from
+layout(location=0) in vec2 a_corner;
+layout(location=1) in vec4 a_color;
+// east and north gaps, 0 not drawn, 1 open, 2 walled
+layout(location=2) in uvec4 a_walls;
+
+layout(set=0, binding=0) uniform CellUniforms {
+	vec2 center;
+	vec2 scale;
+	uint columns;
+	float gap;
+};
+
+layout(location=0) out vec2 v_local;
+layout(location=1) flat out vec4 v_color;
+layout(location=2) flat out uvec2 v_walls;
+
+void main() {
+	uint index = uint(gl_InstanceIndex);
+	vec2 cell = vec2(float(index % columns), float(index / columns));
+
+	v_local = a_corner;
+	v_color = a_color;
+	v_walls = a_walls.xy;
+	gl_Position = vec4((cell + a_corner - center) * scale, 0.0, 1.0);
+}